@@ -0,0 +1,246 @@
+use crate::components::{Camera, Player, Transform, VerticalMotion};
+use crate::ecs_utils::Componentable;
+use crate::geometry::transform::Scaling;
+use crate::input::Input;
+use crate::vector::{Field, MatrixTrait, VectorTrait};
+use specs::prelude::*;
+use std::marker::PhantomData;
+
+// how an animation runs through its keyframes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Playback {
+    // start over from the first keyframe after the last
+    Loop,
+    // run back and forth between the first and last keyframes
+    PingPong,
+    // wait at the first keyframe until the player steps on the shape, then run to the last.
+    // the next time the player steps on, run back to the first
+    Triggered,
+}
+
+#[derive(Clone)]
+pub struct Keyframe<V, M> {
+    pub time: Field,
+    pub transform: Transform<V, M>,
+}
+
+// a keyframed path for the transform of a shape, e.g. a moving platform
+#[derive(Clone)]
+pub struct Animation<V, M> {
+    keyframes: Vec<Keyframe<V, M>>,
+    pub playback: Playback,
+    time: Field,
+    reversed: bool,
+    playing: bool,
+    // whether the player stood on the shape last frame
+    was_triggered: bool,
+}
+
+fn lerp<V: VectorTrait>(a: V, b: V, s: Field) -> V {
+    a * (1.0 - s) + b * s
+}
+
+impl<V: VectorTrait> Animation<V, V::M> {
+    pub fn new(playback: Playback) -> Self {
+        Self {
+            keyframes: Vec::new(),
+            playback,
+            time: 0.0,
+            reversed: false,
+            playing: playback != Playback::Triggered,
+            was_triggered: false,
+        }
+    }
+    // keyframes are kept in order of time
+    pub fn with_keyframe(mut self, time: Field, transform: Transform<V, V::M>) -> Self {
+        let index = self.keyframes.partition_point(|k| k.time <= time);
+        self.keyframes.insert(index, Keyframe { time, transform });
+        self
+    }
+
+    fn duration(&self) -> Field {
+        self.keyframes.last().map(|k| k.time).unwrap_or(0.0)
+    }
+
+    // step the animation forward by dt. triggered is whether the player stands on the shape.
+    // returns the new transform, or None if the animation is standing still
+    pub fn advance(&mut self, dt: Field, triggered: bool) -> Option<Transform<V, V::M>> {
+        let duration = self.duration();
+        if self.playback == Playback::Triggered && triggered && !self.was_triggered && !self.playing
+        {
+            self.playing = true;
+        }
+        self.was_triggered = triggered;
+        if !self.playing || duration <= 0.0 {
+            return None;
+        }
+        let step = if self.reversed { -dt } else { dt };
+        self.time += step;
+        match self.playback {
+            Playback::Loop => self.time = self.time.rem_euclid(duration),
+            Playback::PingPong => {
+                if self.time > duration {
+                    self.time = 2.0 * duration - self.time;
+                    self.reversed = true;
+                } else if self.time < 0.0 {
+                    self.time = -self.time;
+                    self.reversed = false;
+                }
+                self.time = self.time.clamp(0.0, duration);
+            }
+            Playback::Triggered => {
+                if !(0.0..=duration).contains(&self.time) {
+                    self.time = self.time.clamp(0.0, duration);
+                    self.reversed = !self.reversed;
+                    self.playing = false;
+                }
+            }
+        }
+        Some(self.transform_at(self.time))
+    }
+
+    // the transform at time t, interpolated between the keyframes on either side.
+    // frames are interpolated row by row and made orthonormal again
+    pub fn transform_at(&self, t: Field) -> Transform<V, V::M> {
+        let index = self.keyframes.partition_point(|k| k.time <= t);
+        let (a, b) = match index {
+            0 => return self.keyframes[0].transform,
+            i if i == self.keyframes.len() => return self.keyframes[i - 1].transform,
+            i => (&self.keyframes[i - 1], &self.keyframes[i]),
+        };
+        let s = (t - a.time) / (b.time - a.time);
+        let (ta, tb) = (&a.transform, &b.transform);
+        let mut rows: Vec<V> = Vec::new();
+        for (row_a, row_b) in ta.frame.get_rows().into_iter().zip(tb.frame.get_rows()) {
+            let row = rows
+                .iter()
+                .fold(lerp(row_a, row_b, s), |row, &r| row - r * row.dot(r));
+            // fall back on the nearer keyframe where the frames are opposite
+            let row = match row.norm() > 1e-3 {
+                true => row.normalize(),
+                false => match s < 0.5 {
+                    true => row_a,
+                    false => row_b,
+                },
+            };
+            rows.push(row);
+        }
+        let scale = match (ta.scale, tb.scale) {
+            (Scaling::Scalar(x), Scaling::Scalar(y)) => Scaling::Scalar(x * (1.0 - s) + y * s),
+            (sa, sb) => Scaling::Vector(lerp(sa.get_vec(), sb.get_vec(), s)),
+        };
+        Transform {
+            pos: lerp(ta.pos, tb.pos, s),
+            frame: V::M::from_vec_of_vecs(&rows),
+            scale,
+        }
+    }
+}
+
+// where a point fixed to a shape moves to when the shape goes from old to new
+fn carry_point<V: VectorTrait>(old: &Transform<V, V::M>, new: &Transform<V, V::M>, p: V) -> V {
+    new.frame * (old.frame.transpose() * (p - old.pos)) + new.pos
+}
+
+// move animated shapes along their paths. the player is carried by the shape they stand on.
+// modified transforms are picked up by TransformShapeSystem, which updates the rest of the shape
+pub struct AnimationSystem<V>(pub PhantomData<V>);
+
+impl<'a, V> System<'a> for AnimationSystem<V>
+where
+    V: VectorTrait + Componentable,
+    V::M: Componentable,
+{
+    type SystemData = (
+        ReadExpect<'a, Input>,
+        ReadExpect<'a, Player>,
+        Entities<'a>,
+        ReadStorage<'a, VerticalMotion>,
+        WriteStorage<'a, Animation<V, V::M>>,
+        WriteStorage<'a, Transform<V, V::M>>,
+        WriteStorage<'a, Camera<V>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            input,
+            player,
+            entities,
+            vertical_motions,
+            mut animations,
+            mut transforms,
+            mut cameras,
+        ): Self::SystemData,
+    ) {
+        let dt = input.get_dt();
+        let ground = vertical_motions
+            .get(player.0)
+            .and_then(|motion| motion.ground);
+        // joining mutably would flag every animated transform as modified, even standing still
+        let updates: Vec<(Entity, Transform<V, V::M>)> = (&entities, &mut animations)
+            .join()
+            .filter_map(|(e, animation)| {
+                animation
+                    .advance(dt, ground == Some(e))
+                    .map(|transform| (e, transform))
+            })
+            .collect();
+        for (e, new) in updates {
+            let Some(transform) = transforms.get_mut(e) else {
+                continue;
+            };
+            let old = *transform;
+            *transform = new;
+            if ground == Some(e) {
+                if let Some(player_transform) = transforms.get_mut(player.0) {
+                    // only the position is carried, so the player keeps looking the same way
+                    player_transform.pos = carry_point(&old, &new, player_transform.pos);
+                    if let Some(camera) = cameras.get_mut(player.0) {
+                        camera.update(player_transform);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_animation() {
+    use crate::constants::PI;
+    use crate::vector::Vec3;
+    let start = Transform::<Vec3, _>::identity();
+    let end = Transform::pos(Vec3::new(2.0, 0.0, 0.0)).with_rotation(0, 2, PI / 2.0);
+    let keyframes = |playback| {
+        Animation::new(playback)
+            .with_keyframe(1.0, end)
+            .with_keyframe(0.0, start)
+    };
+    // halfway along, the frame has turned halfway
+    let middle = keyframes(Playback::Loop).transform_at(0.5);
+    assert!(Vec3::is_close(middle.pos, Vec3::new(1.0, 0.0, 0.0)));
+    assert!((middle.frame[0].dot(start.frame[0]) - (PI / 4.0).cos()).abs() < 1e-4);
+    assert!((middle.frame[0].norm() - 1.0).abs() < 1e-4);
+
+    let mut looping = keyframes(Playback::Loop);
+    looping.advance(1.25, false);
+    assert!((looping.time - 0.25).abs() < 1e-4);
+
+    let mut ping_pong = keyframes(Playback::PingPong);
+    ping_pong.advance(1.25, false);
+    assert!((ping_pong.time - 0.75).abs() < 1e-4);
+    ping_pong.advance(1.0, false);
+    assert!((ping_pong.time - 0.25).abs() < 1e-4);
+
+    // waits for the player, then runs once in each direction
+    let mut triggered = keyframes(Playback::Triggered);
+    assert!(triggered.advance(0.5, false).is_none());
+    assert!(triggered.advance(0.5, true).is_some());
+    triggered.advance(1.0, true);
+    assert_eq!(triggered.time, 1.0);
+    // staying on doesn't trigger it again
+    assert!(triggered.advance(0.5, true).is_none());
+    triggered.advance(0.5, false);
+    triggered.advance(1.5, true);
+    assert_eq!(triggered.time, 0.0);
+}
@@ -1,11 +1,17 @@
+use crate::animation::{Animation, Playback};
 use crate::coin::Coin;
 use crate::collide::StaticCollider;
-use crate::components::{Cursor, Transform};
-use crate::config::{Config, FuzzLinesConfig, LevelConfig};
+use crate::components::{Cursor, RigidBody, Transform};
+use crate::config::{Config, FuzzLinesConfig, LevelConfig, LightConfig};
 use crate::constants::{COIN_LABEL_STR, CUBE_LABEL_STR, FACE_SCALE, PI};
 use crate::draw::draw_line_collection::DrawLineCollection;
+use crate::draw::light::{LevelLight, Light};
+use crate::draw::shadow::DropShadow;
+use crate::draw::texture::library::{
+    build_texture_library, TextureDescriptor, TextureKind, TextureLabel, TextureLibrary,
+};
 use crate::draw::texture::{color_cube_texture, fuzzy_color_cube_texture};
-use crate::draw::visual_aids::{calc_grid_lines, draw_horizon, draw_sky, draw_stars};
+use crate::draw::visual_aids::calc_grid_lines;
 use crate::draw::{self, FaceTexture, ShapeTexture, Texture};
 use crate::ecs_utils::Componentable;
 use crate::geometry::shape::buildshapes::{
@@ -35,11 +41,20 @@ where
     V::SubV: Componentable,
     V::M: Componentable,
 {
-    shape_builder.build(world).with(Coin).build();
+    let drop_shadow = world.read_resource::<Config>().scene.shadows.coins;
+    let builder = shape_builder.build(world).with(Coin);
+    if drop_shadow {
+        builder.with(DropShadow::<V>::default()).build();
+    } else {
+        builder.build();
+    }
 }
 
-fn build_test_walls<V>(build_shape: &ShapeEntityBuilderV<V>, world: &mut World)
-where
+fn build_test_walls<V>(
+    build_shape: &ShapeEntityBuilderV<V>,
+    textures: &TextureLibrary,
+    world: &mut World,
+) where
     V: VectorTrait + Componentable,
     V::SubV: Componentable,
     V::M: Componentable,
@@ -79,11 +94,19 @@ where
         .build(world)
         .with(StaticCollider)
         .build();
-    let floor = build_shape
+    // the floors share a gradient, tinted to tell them apart
+    let gradient_label = TextureLabel::from_str("GradientTiles");
+    let gradient = textures
+        .get_unwrap(&gradient_label)
+        .clone()
+        .with_color(WHITE);
+    let untinted_floor = build_shape
         .clone()
+        .with_texture_descriptor(&gradient)
+        .with_texture_label(gradient_label)
         .with_translation(-V::one_hot(1) * 1.0)
-        .with_rotation(-1, 1, PI / 2.)
-        .with_color(BLUE);
+        .with_rotation(-1, 1, PI / 2.);
+    let floor = untinted_floor.clone().with_tint(BLUE);
     floor
         .clone()
         .with_translation(-V::one_hot(0) * 2.0)
@@ -102,10 +125,9 @@ where
         .build(world)
         .with(StaticCollider)
         .build();
-    floor
-        .clone()
+    untinted_floor
         .with_translation(V::one_hot(1) * (2.0 * sin) - V::one_hot(-1) * (4.0 + 2.0 * cos))
-        .with_color(MAGENTA)
+        .with_tint(MAGENTA)
         .build(world)
         .with(StaticCollider)
         .build();
@@ -114,20 +136,144 @@ where
         .clone()
         .with_translation(V::one_hot(1) * 1.0)
         .with_rotation(-1, 1, -PI / 2.)
-        .with_color(YELLOW)
+        .with_texture_descriptor(
+            &TextureDescriptor::new(TextureKind::Text {
+                text: "TEST LEVEL".to_string(),
+            })
+            .with_color(YELLOW),
+        )
+        .build(world)
+        .with(StaticCollider)
+        .build();
+}
+// a row of panels behind the orange wall, one for each procedural texture in the library
+fn build_test_texture_panels<V>(
+    build_shape: &ShapeEntityBuilderV<V>,
+    textures: &TextureLibrary,
+    world: &mut World,
+) where
+    V: VectorTrait + Componentable,
+    V::SubV: Componentable,
+    V::M: Componentable,
+{
+    let panels = [
+        ("Stripes", RED),
+        ("Checker", GREEN),
+        ("Shells", ORANGE),
+        ("Noise", CYAN),
+    ];
+    for (i, (name, color)) in panels.into_iter().enumerate() {
+        let label = TextureLabel::from_str(name);
+        let texture = textures.get_unwrap(&label).clone().with_color(color);
+        build_shape
+            .clone()
+            .with_texture_descriptor(&texture)
+            .with_texture_label(label)
+            .with_translation(V::one_hot(0) * 3.0 + V::one_hot(-1) * (2.5 * i as Field - 3.75))
+            .with_rotation(0, -1, PI / 2.)
+            .build(world)
+            .with(StaticCollider)
+            .build();
+    }
+}
+
+// moving platforms past the floors, one for each kind of playback
+fn build_test_platforms<V>(build_shape: &ShapeEntityBuilderV<V>, world: &mut World)
+where
+    V: VectorTrait + Componentable,
+    V::SubV: Componentable,
+    V::M: Componentable,
+{
+    let platform = |offset: V, tint: Color| {
+        build_shape
+            .clone()
+            .with_tint(tint)
+            .with_translation(V::one_hot(1) * -1.0 + offset)
+            .with_rotation(-1, 1, PI / 2.)
+    };
+    // an elevator that rises when stepped on, and comes down the next time
+    let elevator = platform(-V::one_hot(0) * 4.0, YELLOW);
+    let base = elevator.transformation;
+    let animation = Animation::new(Playback::Triggered)
+        .with_keyframe(0.0, base)
+        .with_keyframe(2.0, base.with_translation(V::one_hot(1) * 2.0));
+    elevator
+        .build(world)
+        .with(StaticCollider)
+        .with(animation)
+        .build();
+    // back and forth along the last axis
+    let shuttle = platform(-V::one_hot(0) * 6.0, CYAN);
+    let base = shuttle.transformation;
+    let animation = Animation::new(Playback::PingPong)
+        .with_keyframe(0.0, base)
+        .with_keyframe(3.0, base.with_translation(-V::one_hot(-1) * 4.0));
+    shuttle
+        .build(world)
+        .with(StaticCollider)
+        .with(animation)
+        .build();
+    // around a square
+    let carousel = platform(-V::one_hot(0) * 8.0, MAGENTA);
+    let base = carousel.transformation;
+    let animation = [
+        V::zero(),
+        -V::one_hot(0) * 2.0,
+        -V::one_hot(0) * 2.0 - V::one_hot(-1) * 2.0,
+        -V::one_hot(-1) * 2.0,
+        V::zero(),
+    ]
+    .into_iter()
+    .enumerate()
+    .fold(Animation::new(Playback::Loop), |animation, (k, offset)| {
+        animation.with_keyframe(1.5 * k as Field, base.with_translation(offset))
+    });
+    carousel
         .build(world)
         .with(StaticCollider)
+        .with(animation)
         .build();
 }
-pub fn build_test_level<V>(world: &mut World, ref_shapes: &mut RefShapes<V>)
+
+// crates on the floors for the player to push around
+fn build_test_crates<V>(ref_shapes: &RefShapes<V>, world: &mut World)
 where
     V: VectorTrait + Componentable,
     V::SubV: Componentable,
     V::M: Componentable,
 {
-    let (n_divisions, frame_vertis) = match V::DIM {
-        3 => (vec![4, 4], vec![1, 3]),
-        4 => (vec![4, 4, 4], vec![1, 3, 4]),
+    let crate_builder = ShapeEntityBuilder::new_convex_from_ref_shape(
+        ref_shapes,
+        ShapeLabel::from_str(CUBE_LABEL_STR),
+    )
+    .with_scale(Scaling::Scalar(0.5))
+    .with_color(ORANGE);
+    for pos in [
+        -V::one_hot(0) * 2.0 - V::one_hot(1) * 0.5,
+        -V::one_hot(0) * 2.0 - V::one_hot(-1) * 2.0,
+    ] {
+        crate_builder
+            .clone()
+            .with_translation(pos)
+            .build(world)
+            .with(StaticCollider)
+            .with(RigidBody::<V>::new(true, 0.3))
+            .build();
+    }
+}
+
+pub fn build_test_level<V>(
+    world: &mut World,
+    ref_shapes: &mut RefShapes<V>,
+    textures: &TextureLibrary,
+) where
+    V: VectorTrait + Componentable,
+    V::SubV: Componentable,
+    V::M: Componentable,
+{
+    let frame_vertis = match V::DIM {
+        3 => vec![1, 3],
+        4 => vec![1, 3, 4],
         _ => panic!("Cannot build test level in {} dimensions.", { V::DIM }),
     };
     let sub_wall = ShapeBuilder::<V::SubV>::build_cube(2.0).build();
@@ -137,22 +283,35 @@ where
     let build_shape: ShapeEntityBuilderV<V> =
         ShapeEntityBuilder::new_face_from_ref_shape(ref_shapes, wall_single_face, wall_label)
             .with_face_texture(FaceTexture {
-                texture: draw::Texture::make_tile_texture(&[0.8], &n_divisions),
+                texture: textures.build(&TextureLabel::from_str("Tiles")),
                 texture_mapping: Some(draw::TextureMapping {
                     origin_verti: 0,
                     frame_vertis,
                 }),
-            });
-    build_test_walls(&build_shape, world);
+            })
+            .with_texture_label(TextureLabel::from_str("Tiles"));
+    build_test_walls(&build_shape, textures, world);
+    build_test_texture_panels(&build_shape, textures, world);
+    build_test_platforms(&build_shape, world);
+    build_test_crates(ref_shapes, world);
+}
+
+// a low light from the side, so the tilted walls are shaded differently
+fn test_level_light() -> LightConfig {
+    LightConfig {
+        enabled: true,
+        direction: vec![1.0, 0.6, -0.4, 0.3],
+        ambient: 0.35,
+    }
 }
 
 pub fn build_fun_level<V: VectorTrait>(
-    fuzz_config: FuzzLinesConfig,
     ref_shapes: &mut RefShapes<V>,
+    textures: &TextureLibrary,
 ) -> Vec<ShapeEntityBuilderV<V>> {
-    let (n_divisions, frame_vertis) = match V::DIM {
-        3 => (vec![2, 2], vec![1, 3]),
-        4 => (vec![2, 2, 2], vec![1, 3, 4]),
+    let frame_vertis = match V::DIM {
+        3 => vec![1, 3],
+        4 => vec![1, 3, 4],
         _ => panic!("Cannot build test level in {} dimensions.", { V::DIM }),
     };
     let len = 4.0;
@@ -164,13 +323,13 @@ pub fn build_fun_level<V: VectorTrait>(
     let wall_builder =
         ShapeEntityBuilder::new_face_from_ref_shape(ref_shapes, wall_single_face, wall_label)
             .with_face_texture(FaceTexture {
-                texture: draw::Texture::make_tile_texture(&[0.8], &n_divisions)
-                    .merged_with(&Texture::make_fuzz_texture(fuzz_config.face_num)),
+                texture: textures.build(&TextureLabel::from_str("FuzzyTiles")),
                 texture_mapping: Some(draw::TextureMapping {
                     origin_verti: 0,
                     frame_vertis,
                 }),
-            });
+            })
+            .with_texture_label(TextureLabel::from_str("FuzzyTiles"));
     let (floor, floor_single_face) = convex_shape_to_face_shape(sub_cube, true);
     let floor_label = ShapeLabel("Floor".to_string());
     ref_shapes.insert(floor_label.clone(), floor);
@@ -230,53 +389,50 @@ where
 {
     let config: Config = (*world.read_resource::<Config>()).clone();
     let mut ref_shapes = build_shape_library::<V>();
+    let textures = build_texture_library(config.fuzz_lines.face_num);
 
-    match config.scene.level {
-        LevelConfig::Level1 => build_lvl_1(
-            world,
-            &ref_shapes,
-            config.scene.level_1.unwrap_or_default().open_center,
-        ),
-        LevelConfig::Test1 => build_test_level(world, &mut ref_shapes),
+    // levels may have their own light
+    let level_light = match config.scene.level {
+        LevelConfig::Level1 => {
+            build_lvl_1(
+                world,
+                &ref_shapes,
+                config.scene.level_1.unwrap_or_default().open_center,
+            );
+            None
+        }
+        LevelConfig::Test1 => {
+            build_test_level(world, &mut ref_shapes, &textures);
+            Some(test_level_light())
+        }
         LevelConfig::Test2 => {
-            build_fun_level(config.fuzz_lines, &mut ref_shapes)
+            build_fun_level(&mut ref_shapes, &textures)
                 .into_iter()
                 .for_each(|b| insert_static_collider(world, b));
+            None
         }
-        LevelConfig::Empty => (),
+        LevelConfig::Empty => None,
     };
     build_empty_level::<V>(world);
     init_player(world, V::zero());
     world.insert(ref_shapes);
+    world.insert(textures);
+    let level_light = LevelLight(level_light.unwrap_or(config.scene.light));
+    world.insert(Light::<V>::from_config(&level_light.0));
+    world.insert(level_light);
 }
 
 pub fn build_empty_level<V: VectorTrait + Componentable>(world: &mut World) {
-    let config: Config = (*world.read_resource::<Config>()).clone();
-    let scene_config = config.scene;
-    vec![
-        DrawLineCollection::from_lines(
-            calc_grid_lines(V::one_hot(1) * (-1.0) + (V::ones() * 0.5), 1.0, 2),
-            WHITE.set_alpha(0.2),
-        ),
-        DrawLineCollection(draw_sky::<V>(config.fuzz_lines.sky_num)),
-        DrawLineCollection::from_lines(
-            draw_horizon::<V>(config.fuzz_lines.horizon_num),
-            ORANGE.set_alpha(0.5),
-        ),
-        DrawLineCollection(draw_stars::<V>()),
-    ]
-    .into_iter()
-    .zip([
-        scene_config.grid,
-        scene_config.sky,
-        scene_config.horizon,
-        scene_config.stars,
-    ])
-    .for_each(|(dlc, enabled)| {
-        if enabled {
-            world.create_entity().with(dlc).build();
-        }
-    });
+    // the sky, horizon and stars follow the player. see draw::sky
+    if world.read_resource::<Config>().scene.grid {
+        world
+            .create_entity()
+            .with(DrawLineCollection::from_lines(
+                calc_grid_lines(V::one_hot(1) * (-1.0) + (V::ones() * 0.5), 1.0, 2),
+                WHITE.set_alpha(0.2),
+            ))
+            .build();
+    }
 }
 
 pub fn build_corridor_cross<V: VectorTrait>(
@@ -434,6 +590,13 @@ where
 {
     let transform = Transform::identity().with_translation(pos);
     crate::player::build_player(world, &transform, None);
+    if world.read_resource::<Config>().scene.shadows.player {
+        let player = world.read_resource::<crate::player::Player>().0;
+        world
+            .write_storage::<DropShadow<V>>()
+            .insert(player, DropShadow::default())
+            .expect("Could not add drop shadow to player");
+    }
     init_cursor::<V>(world);
 }
 pub fn init_cursor<V>(world: &mut World)
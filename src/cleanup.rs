@@ -1,3 +1,4 @@
+use crate::draw::clipping::bsp::BspTree;
 use crate::draw::clipping::ShapeClipState;
 use crate::ecs_utils::Componentable;
 use crate::spatial_hash::SpatialHashSet;
@@ -22,14 +23,19 @@ impl<'a, V: Componentable + VectorTrait> System<'a> for ShapeCleanupSystem<V> {
         Write<'a, DeletedEntities>,
         WriteStorage<'a, ShapeClipState<V>>,
         WriteExpect<'a, SpatialHashSet<V, Entity>>,
+        Write<'a, BspTree<V, Entity>>,
         Entities<'a>,
     );
 
-    fn run(&mut self, (mut deleted, mut shape_clip, mut hash, entities): Self::SystemData) {
+    fn run(
+        &mut self,
+        (mut deleted, mut shape_clip, mut hash, mut bsp_tree, entities): Self::SystemData,
+    ) {
         let len = deleted.0.len();
         for e in deleted.0.drain(0..len) {
             //this is a little slow since we need to check all cells, but we don't expect this to occur often
             hash.remove_from_all(&e); //remove from spatial hash
+            bsp_tree.remove(&e);
 
             for clip in (&mut shape_clip).join() {
                 clip.remove(&e);
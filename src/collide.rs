@@ -1,10 +1,12 @@
 pub mod bbox;
+pub mod rigid_body;
 pub mod systems;
 
 use crate::components::{Camera, Convex, Player, Shape, ShapeType, Transform, Transformable};
-use crate::constants::PLAYER_COLLIDE_DISTANCE;
+use crate::constants::{GROUND_NORMAL_MIN, PLAYER_COLLIDE_DISTANCE};
 use crate::ecs_utils::{Componentable, ModSystem};
 use crate::geometry::transform::Scaling;
+use crate::geometry::Plane;
 use crate::input::key_map::PRINT_DEBUG;
 use crate::input::Input;
 use crate::spatial_hash::{HashInt, SpatialHashSet};
@@ -15,7 +17,10 @@ use specs::Component;
 use std::marker::PhantomData;
 
 pub use self::bbox::{BBox, HasBBox};
-use self::systems::BBoxHashingSystem;
+
+// steps of sliding along faces in one move
+const MAX_SLIDES: usize = 4;
+const SWEEP_EPSILON: Field = 1e-4;
 
 #[derive(Clone, Component)]
 #[storage(VecStorage)]
@@ -81,13 +86,18 @@ pub fn create_spatial_hash<V: VectorTrait + Componentable>(world: &mut World) {
 
     //println!("Min/max: {},{}",min,max);
     //println!("Longest sides {}",max_lengths);
-    world.insert(SpatialHashSet::<V, Entity>::new(
+    let mut hash = SpatialHashSet::<V, Entity>::new(
         min * 1.5, //make bounds slightly larger than farthest points
         max * 1.5,
         max_lengths * 1.1, //make cell size slightly larger than largest bbox dimensions
-    ));
-    //enter bboxes into hash set
-    //BBoxHashingSystem(ModSystem::typed_default(PhantomData::<V>)).run_now(&world);
+    );
+    //enter bboxes into hash set now. BBoxHashingSystem runs after the player moves, so an empty
+    //hash would let the player fall through the floor on the first frame
+    update_static_bboxes(
+        &mut hash,
+        (&world.read_component::<BBox<V>>(), &world.entities()).join(),
+    );
+    world.insert(hash);
 }
 
 fn get_bbox_cells<V: VectorTrait>(
@@ -111,7 +121,7 @@ fn get_bbox_cells<V: VectorTrait>(
 //this means that we should have the cell sizes larger than the longest object, for each axis
 //this may be problematic if we want to consider entities with size comparable to the scene size - then the hash map is useless
 
-fn get_entities_in_bbox<V: VectorTrait>(
+pub(crate) fn get_entities_in_bbox<V: VectorTrait>(
     bbox: &BBox<V>,
     hash: &SpatialHashSet<V, Entity>,
 ) -> Vec<Entity> {
@@ -157,38 +167,16 @@ pub fn update_static_bboxes<'a, V: VectorTrait + 'a, I>(
     }
 }
 
-fn get_bits(n: HashInt, n_bits: HashInt) -> impl Iterator<Item = HashInt> {
-    //might be more sensible to be bool?
-    (0..n_bits).map(move |k| n.rotate_right(k) % 2)
-}
-//could memoize results here if too slow. but should be fast
-//assumes entries of dcoords are 0 or 1
+//cells offset from the base cell by up to dcoords along each axis
 fn get_dcoords_dcells(dcoords: &[HashInt], mult: &[HashInt]) -> Vec<HashInt> {
-    //let dim = dcoords.len();
-    assert!(dcoords.iter().all(|&d| d == 0 || d == 1));
-    let dpos: Vec<usize> = dcoords
+    dcoords
         .iter()
-        .enumerate()
-        .filter_map(|(i, d)| match d {
-            1 => Some(i),
-            _ => None,
+        .zip(mult.iter())
+        .fold(vec![0], |cells, (&d, &m)| {
+            (0..=d)
+                .flat_map(|k| cells.iter().map(move |&cell| cell + k * m))
+                .collect()
         })
-        .collect();
-
-    let box_dim = dcoords.iter().sum();
-    let kmax: HashInt = (2 as HashInt).pow(box_dim);
-    let mut out_vec: Vec<HashInt> = vec![];
-
-    for k in 0..kmax {
-        let k_bits = get_bits(k, box_dim);
-        let cell = k_bits
-            .zip(dpos.iter())
-            .map(|(kb, &d)| kb * mult[d])
-            .sum::<HashInt>();
-        //println!("CELL: {:?} vs {:?}",cell,k);
-        out_vec.push(cell)
-    }
-    out_vec
 }
 
 pub fn move_player<V: VectorTrait>(
@@ -208,49 +196,248 @@ pub fn move_player<V: VectorTrait>(
 }
 
 pub fn update_player_bbox<V: VectorTrait>(player_bbox: &mut BBox<V>, player_pos: V) {
-    player_bbox.min = player_pos - V::constant(0.2);
-    player_bbox.max = player_pos + V::constant(-0.2);
+    player_bbox.min = player_pos - V::constant(PLAYER_COLLIDE_DISTANCE);
+    player_bbox.max = player_pos + V::constant(PLAYER_COLLIDE_DISTANCE);
+}
+
+// the player's bbox stretched to cover its next move
+pub fn swept_bbox<V: VectorTrait>(bbox: &BBox<V>, dpos: Option<V>) -> BBox<V> {
+    let dpos = dpos.unwrap_or_else(V::zero);
+    BBox {
+        min: bbox.min.zip_map(bbox.min + dpos, Field::min),
+        max: bbox.max.zip_map(bbox.max + dpos, Field::max),
+    }
+}
+
+// faces the player touched while moving
+#[derive(Default)]
+pub struct Contacts {
+    // the floor below the player
+    pub ground: Option<Entity>,
+    // a ceiling above the player
+    pub ceiling: bool,
+}
+
+// a static shape as the region inside its planes, which the player can't enter
+struct Collider<V> {
+    entity: Entity,
+    planes: Vec<Plane<V>>,
+    verts: Vec<V>,
+    // one sided faces can be entered from behind, through their back plane planes[1]
+    one_sided: bool,
+}
+impl<V: VectorTrait> Collider<V> {
+    fn new(shape: &Shape<V>, shape_type: &ShapeType<V>, entity: Entity) -> Self {
+        match shape_type {
+            ShapeType::Convex(_) => Self {
+                entity,
+                planes: shape
+                    .faces
+                    .iter()
+                    .map(|face| face.plane().clone())
+                    .collect(),
+                verts: shape.verts.clone(),
+                one_sided: false,
+            },
+            ShapeType::SingleFace(single_face) => Self {
+                entity,
+                planes: single_face.slab_planes(shape.faces[0].plane()),
+                verts: shape.verts.clone(),
+                one_sided: !single_face.two_sided,
+            },
+        }
+    }
+    fn translated(&self, dpos: V) -> Self {
+        Self {
+            entity: self.entity,
+            planes: self
+                .planes
+                .iter()
+                .map(|plane| Plane {
+                    normal: plane.normal,
+                    threshold: plane.threshold + plane.normal.dot(dpos),
+                })
+                .collect(),
+            verts: self.verts.iter().map(|&v| v + dpos).collect(),
+            one_sided: self.one_sided,
+        }
+    }
+    fn sweep_planes(&self, pos: V, dpos: V, radius: Field) -> Option<(Field, V)> {
+        sweep_sphere(&self.planes, pos, dpos, radius)
+            .filter(|&(_, i)| !(self.one_sided && i == 1))
+            .map(|(t, i)| (t, self.planes[i].normal))
+    }
+    // where the player first touches the collider when moving from pos by dpos.
+    // returns the fraction of dpos moved and the normal of the face touched
+    fn sweep(&self, pos: V, dpos: V) -> Option<(Field, V, Entity)> {
+        self.sweep_planes(pos, dpos, PLAYER_COLLIDE_DISTANCE)
+            .map(|(t, normal)| (t, normal, self.entity))
+    }
+    // where another shape first touches the collider when moving by dpos. the vertices of the
+    // body are swept into the collider, and the vertices of the collider into the body moving
+    // the other way. edges crossing edges are missed, which is rare for boxes on floors and walls
+    fn sweep_body(&self, body: &Collider<V>, dpos: V) -> Option<(Field, V, Entity)> {
+        let body_verts = body
+            .verts
+            .iter()
+            .filter_map(|&v| self.sweep_planes(v, dpos, 0.0));
+        let collider_verts = self.verts.iter().filter_map(|&v| {
+            sweep_sphere(&body.planes, v, -dpos, 0.0).map(|(t, i)| (t, -body.planes[i].normal))
+        });
+        body_verts
+            .chain(collider_verts)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(t, normal)| (t, normal, self.entity))
+    }
 }
 
+// first touch of a sphere moving from pos by dpos with the region inside all the planes,
+// growing each plane by the radius. returns the fraction of dpos moved and the index of the plane.
+// a sphere that starts inside touches the nearest plane at once, unless it is moving away from it
+fn sweep_sphere<V: VectorTrait>(
+    planes: &[Plane<V>],
+    pos: V,
+    dpos: V,
+    radius: Field,
+) -> Option<(Field, usize)> {
+    let distance = |plane: &Plane<V>| plane.point_signed_distance(pos) - radius;
+    if planes.iter().all(|plane| distance(plane) <= 0.0) {
+        let (i, plane) = planes
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))?;
+        return (plane.normal.dot(dpos) < 0.0).then_some((0.0, i));
+    }
+    // clip the path to the planes, keeping the last plane it enters and the first it exits
+    let mut enter: Option<(Field, usize)> = None;
+    let mut exit = Field::INFINITY;
+    for (i, plane) in planes.iter().enumerate() {
+        let speed = plane.normal.dot(dpos);
+        if speed.abs() < SWEEP_EPSILON {
+            if distance(plane) > 0.0 {
+                return None;
+            }
+            continue;
+        }
+        let t = -distance(plane) / speed;
+        if speed > 0.0 {
+            exit = exit.min(t);
+        } else if enter.is_none_or(|(t_enter, _)| t > t_enter) {
+            enter = Some((t, i));
+        }
+    }
+    // a path that only meets the region behind its start doesn't touch it
+    enter.filter(|&(t, _)| (0.0..=1.0).contains(&t) && t <= exit && exit >= 0.0)
+}
+
+// moves by dpos as far as possible, sliding along the faces touched. sweep gives the first
+// touch for the remaining move, starting from the move made so far. returns the move made
+fn slide<V: VectorTrait>(
+    dpos: V,
+    sweep: impl Fn(V, V) -> Option<(Field, V, Entity)>,
+    mut on_hit: impl FnMut(V, Entity),
+) -> V {
+    let mut moved = V::zero();
+    let mut remaining = dpos;
+    // movement left after the last slide is dropped, so things rest in corners
+    for _ in 0..MAX_SLIDES {
+        let Some((t, normal, entity)) = sweep(moved, remaining) else {
+            return moved + remaining;
+        };
+        // stop just off the face, so the next sweep starts outside it
+        moved = moved + remaining * t + normal * SWEEP_EPSILON;
+        remaining = remaining * (1.0 - t);
+        remaining = remaining - normal * normal.dot(remaining).min(0.0);
+        on_hit(normal, entity);
+    }
+    moved
+}
+
+fn first_hit<V: VectorTrait>(
+    hits: impl Iterator<Item = (Field, V, Entity)>,
+) -> Option<(Field, V, Entity)> {
+    hits.min_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+// moves the player by next_dpos as far as possible without entering any of the shapes,
+// sliding along the faces it touches. the whole path is checked, so fast moves can't pass
+// through thin faces. returns the floors and ceilings touched
 pub fn check_player_static_collisions<'a, I, V: VectorTrait + 'a>(
     move_next: &mut MoveNext<V>,
     player_pos: V,
     shape_iter: I,
-) where
-    I: Iterator<Item = (&'a Shape<V>, &'a ShapeType<V>)>,
+) -> Contacts
+where
+    I: Iterator<Item = (&'a Shape<V>, &'a ShapeType<V>, Entity)>,
 {
-    if let MoveNext {
-        next_dpos: Some(_next_dpos),
-        can_move: Some(true),
-    } = move_next
-    {
-        for (shape, shape_type) in shape_iter {
-            let next_dpos = move_next.next_dpos.unwrap();
-            //this is more convoluted than it needs to be
-            let (normal, dist) = shape.point_normal_distance(player_pos);
-            if match shape_type {
-                ShapeType::SingleFace(single_face) => {
-                    if single_face.two_sided {
-                        (dist.abs() < PLAYER_COLLIDE_DISTANCE)
-                            & (single_face.subface_normal_distance(player_pos).1
-                                < PLAYER_COLLIDE_DISTANCE)
-                    } else {
-                        (dist < PLAYER_COLLIDE_DISTANCE)
-                            & (single_face.subface_normal_distance(player_pos).1
-                                < PLAYER_COLLIDE_DISTANCE)
-                    }
-                }
-                ShapeType::Convex(_) => dist < PLAYER_COLLIDE_DISTANCE,
-            } {
-                //push player away along normal of nearest face (projects out -normal)
-                //but i use abs here to guarantee the face always repels the player
-                let new_dpos = next_dpos + (normal * dist.signum()) * (normal.dot(next_dpos).abs());
-
-                move_next.next_dpos = Some(new_dpos);
-                //println!("{}",normal);
+    let mut contacts = Contacts::default();
+    let (Some(dpos), Some(true)) = (move_next.next_dpos, move_next.can_move) else {
+        return contacts;
+    };
+    let colliders = shape_iter
+        .map(|(shape, shape_type, entity)| Collider::new(shape, shape_type, entity))
+        .collect_vec();
+    let moved = slide(
+        dpos,
+        |moved, remaining| {
+            first_hit(
+                colliders
+                    .iter()
+                    .filter_map(|collider| collider.sweep(player_pos + moved, remaining)),
+            )
+        },
+        |normal, entity| {
+            if normal[1] > GROUND_NORMAL_MIN {
+                contacts.ground = Some(entity);
             }
-        }
-    }
+            contacts.ceiling |= normal[1] < -GROUND_NORMAL_MIN;
+        },
+    );
+    move_next.next_dpos = Some(moved);
+    contacts
+}
+
+// the direction the player pushes a shape in when moving from pos by dpos, along the ground
+// and away from the player. shapes the player doesn't walk into, stands on or hits from below
+// aren't pushed
+pub fn push_direction<V: VectorTrait>(
+    body: (&Shape<V>, &ShapeType<V>, Entity),
+    pos: V,
+    dpos: V,
+) -> Option<V> {
+    let (_, normal, _) = Collider::new(body.0, body.1, body.2).sweep(pos, dpos)?;
+    (normal[1].abs() <= GROUND_NORMAL_MIN)
+        .then(|| -(normal - V::one_hot(1) * normal[1]).normalize())
+}
+
+// moves a shape by dpos as far as possible without entering any of the other shapes,
+// sliding along the faces it touches. returns the move made and the normals of the faces touched
+pub fn check_body_static_collisions<'a, I, V: VectorTrait + 'a>(
+    body: (&Shape<V>, &ShapeType<V>, Entity),
+    dpos: V,
+    shape_iter: I,
+) -> (V, Vec<V>)
+where
+    I: Iterator<Item = (&'a Shape<V>, &'a ShapeType<V>, Entity)>,
+{
+    let body = Collider::new(body.0, body.1, body.2);
+    let colliders = shape_iter
+        .map(|(shape, shape_type, entity)| Collider::new(shape, shape_type, entity))
+        .collect_vec();
+    let mut normals = Vec::new();
+    let moved = slide(
+        dpos,
+        |moved, remaining| {
+            let body = body.translated(moved);
+            first_hit(
+                colliders
+                    .iter()
+                    .filter_map(|collider| collider.sweep_body(&body, remaining)),
+            )
+        },
+        |normal, _| normals.push(normal),
+    );
+    (moved, normals)
 }
 
 #[test]
@@ -270,4 +457,72 @@ fn dcoords_cells_test() {
     assert_eq!(get_dcoords_dcells(&vec![1, 1, 0], &mult), vec![0, 1, 2, 3]);
     assert_eq!(get_dcoords_dcells(&vec![0, 1, 1], &mult), vec![0, 2, 4, 6]);
     assert_eq!(get_dcoords_dcells(&vec![1, 0, 1], &mult), vec![0, 1, 4, 5]);
+    // long moves can span more than two cells along an axis
+    assert_eq!(
+        get_dcoords_dcells(&[2, 0, 1], &mult),
+        vec![0, 1, 2, 4, 5, 6]
+    );
+}
+
+#[test]
+fn test_swept_collisions() {
+    use crate::geometry::shape::buildshapes::{convex_shape_to_face_shape, ShapeBuilder};
+    use crate::vector::{Vec2, Vec3};
+    let entity = World::new().create_entity().build();
+    let move_by = |dpos: Vec3, pos: Vec3, shape: &Shape<Vec3>, shape_type: &ShapeType<Vec3>| {
+        let mut move_next = MoveNext {
+            next_dpos: Some(dpos),
+            can_move: Some(true),
+        };
+        let contacts = check_player_static_collisions(
+            &mut move_next,
+            pos,
+            std::iter::once((shape, shape_type, entity)),
+        );
+        (move_next.next_dpos.unwrap(), contacts)
+    };
+    // a fast move stops at a thin wall in the plane z = 0, instead of passing through it
+    let (wall, wall_face) =
+        convex_shape_to_face_shape::<Vec3>(ShapeBuilder::<Vec2>::build_cube(2.0).build(), true);
+    let wall_type = ShapeType::SingleFace(wall_face);
+    let start = Vec3::new(0.0, 0.0, -1.0);
+    let (dpos, _) = move_by(Vec3::new(0.0, 0.0, 10.0), start, &wall, &wall_type);
+    assert!((start + dpos)[2] < -PLAYER_COLLIDE_DISTANCE + 1e-3);
+    assert!((start + dpos)[2] > -PLAYER_COLLIDE_DISTANCE - 1e-3);
+    // moving past the edge of the wall is not blocked
+    let (dpos, _) = move_by(
+        Vec3::new(0.0, 0.0, 10.0),
+        Vec3::new(2.0, 0.0, -1.0),
+        &wall,
+        &wall_type,
+    );
+    assert!(Vec3::is_close(dpos, Vec3::new(0.0, 0.0, 10.0)));
+
+    // falling diagonally onto a floor slides along it and touches the ground
+    let mut floor = ShapeBuilder::<Vec3>::build_cube(2.0).build();
+    floor.update_from_ref(&floor.clone(), &Transform::pos(Vec3::new(0.0, -2.0, 0.0)));
+    let floor_type = ShapeType::Convex(Convex::new(&floor));
+    let (dpos, contacts) = move_by(
+        Vec3::new(0.5, -5.0, 0.0),
+        Vec3::new(0.0, 0.0, 0.0),
+        &floor,
+        &floor_type,
+    );
+    assert!(contacts.ground == Some(entity) && !contacts.ceiling);
+    assert!((dpos[1] - (PLAYER_COLLIDE_DISTANCE - 1.0)).abs() < 1e-3);
+    assert!(dpos[0] > 0.0);
+    // moving away from a wall is not blocked
+    let away = Vec3::new(0.0, 0.0, 0.1);
+    let (dpos, _) = move_by(away, Vec3::new(0.0, -2.0, 1.5), &floor, &floor_type);
+    assert!(Vec3::is_close(dpos, away));
+    // and neither is jumping up off a floor
+    let up = Vec3::new(0.0, 0.05, 0.0);
+    let (dpos, contacts) = move_by(
+        up,
+        Vec3::new(0.0, PLAYER_COLLIDE_DISTANCE - 1.0 + 1e-4, 0.0),
+        &floor,
+        &floor_type,
+    );
+    assert!(Vec3::is_close(dpos, up));
+    assert!(contacts.ground.is_none());
 }
@@ -0,0 +1,136 @@
+use crate::config::PhysicsConfig;
+use crate::constants::GROUND_NORMAL_MIN;
+use crate::vector::{Field, VectorTrait};
+
+// bounces slower than this come to rest instead, so bodies don't jitter on the ground
+const REST_SPEED: Field = 0.5;
+
+// a shape moved by physics. it collides with static colliders, and is pushed by the player
+pub struct RigidBody<V> {
+    pub velocity: V,
+    // whether the body falls
+    pub gravity: bool,
+    // fraction of the speed into a face kept when bouncing off it
+    pub restitution: Field,
+}
+impl<V: VectorTrait> RigidBody<V> {
+    pub fn new(gravity: bool, restitution: Field) -> Self {
+        Self {
+            velocity: V::zero(),
+            gravity,
+            restitution,
+        }
+    }
+    pub fn accelerate(&mut self, config: &PhysicsConfig, dt: Field) {
+        if self.gravity {
+            let fall = (self.velocity[1] - config.gravity * dt).max(-config.terminal_velocity);
+            self.velocity = self.velocity + V::one_hot(1) * (fall - self.velocity[1]);
+        }
+    }
+    // speed the body up to at least speed along direction
+    pub fn push(&mut self, direction: V, speed: Field) {
+        let current = self.velocity.dot(direction);
+        if speed > current {
+            self.velocity = self.velocity + direction * (speed - current);
+        }
+    }
+    // update the velocity from the faces touched while moving. the body bounces off each face,
+    // and slows down while resting on the ground
+    pub fn bounce(&mut self, normals: &[V], config: &PhysicsConfig, dt: Field) {
+        let mut grounded = false;
+        for &normal in normals {
+            let speed = self.velocity.dot(normal);
+            if speed < 0.0 {
+                let restitution = match -speed > REST_SPEED {
+                    true => self.restitution,
+                    false => 0.0,
+                };
+                self.velocity = self.velocity - normal * (speed * (1.0 + restitution));
+            }
+            grounded |= normal[1] > GROUND_NORMAL_MIN;
+        }
+        if grounded {
+            let up = V::one_hot(1);
+            let sliding = self.velocity - up * self.velocity[1];
+            let speed = sliding.norm();
+            if speed > 0.0 {
+                let slowed = (speed - config.friction * dt).max(0.0);
+                self.velocity = self.velocity - sliding * (1.0 - slowed / speed);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_rigid_body() {
+    use super::{check_body_static_collisions, push_direction};
+    use crate::components::{Convex, Shape, ShapeType, Transform};
+    use crate::constants::PLAYER_COLLIDE_DISTANCE;
+    use crate::geometry::shape::buildshapes::ShapeBuilder;
+    use crate::vector::Vec3;
+    use specs::prelude::*;
+    let config = PhysicsConfig::default();
+    let mut world = World::new();
+    let (body_entity, floor_entity) =
+        (world.create_entity().build(), world.create_entity().build());
+    let cube_at = |pos: Vec3, size: Field| {
+        let mut shape = ShapeBuilder::<Vec3>::build_cube(size).build();
+        shape.update_from_ref(&shape.clone(), &Transform::pos(pos));
+        let shape_type = ShapeType::Convex(Convex::new(&shape));
+        (shape, shape_type)
+    };
+    // a crate above a floor with its top at y = 0
+    let (body, body_type) = cube_at(Vec3::new(0.0, 1.0, 0.0), 1.0);
+    let (floor, floor_type) = cube_at(Vec3::new(0.0, -1.0, 0.0), 2.0);
+    let (dpos, normals) = check_body_static_collisions(
+        (&body, &body_type, body_entity),
+        Vec3::new(0.5, -2.0, 0.0),
+        std::iter::once((&floor, &floor_type, floor_entity)),
+    );
+    // lands on the floor and slides along it
+    assert!((dpos[1] + 0.5).abs() < 1e-3);
+    assert!(dpos[0] > 0.0);
+    assert!(normals
+        .iter()
+        .any(|n| Vec3::is_close(*n, Vec3::new(0.0, 1.0, 0.0))));
+
+    // bounces off the floor, until it is slow enough to rest
+    let mut rigid_body = RigidBody::<Vec3>::new(true, 0.5);
+    rigid_body.velocity = Vec3::new(0.0, -4.0, 0.0);
+    rigid_body.bounce(&normals, &config, 0.0);
+    assert!(Vec3::is_close(
+        rigid_body.velocity,
+        Vec3::new(0.0, 2.0, 0.0)
+    ));
+    rigid_body.velocity = Vec3::new(1.0, -0.1, 0.0);
+    rigid_body.bounce(&normals, &config, 0.1);
+    assert!(rigid_body.velocity[1].abs() < 1e-4);
+    // friction slows it down while on the ground
+    assert!(rigid_body.velocity[0] < 1.0);
+    // moving away from a wall is not blocked
+    let (dpos, normals) = check_body_static_collisions(
+        (&floor, &floor_type, floor_entity),
+        Vec3::new(0.0, -0.1, 0.0),
+        std::iter::once((&body, &body_type, body_entity)),
+    );
+    assert!(Vec3::is_close(dpos, Vec3::new(0.0, -0.1, 0.0)) && normals.is_empty());
+
+    // the player pushes crates ahead, but not crates behind
+    let (body, body_type) = cube_at(Vec3::new(0.0, 0.5, 0.0), 1.0);
+    let step = Vec3::new(0.0, 0.0, 0.1);
+    let ahead = Vec3::new(0.0, 0.5, -0.5 - PLAYER_COLLIDE_DISTANCE - 0.05);
+    let push = push_direction((&body, &body_type, body_entity), ahead, step);
+    assert!(push.is_some_and(|direction| Vec3::is_close(direction, Vec3::new(0.0, 0.0, 1.0))));
+    let behind = Vec3::new(0.0, 0.5, 0.5 + PLAYER_COLLIDE_DISTANCE + 0.05);
+    assert!(push_direction((&body, &body_type, body_entity), behind, step).is_none());
+    // nor crates it stands on
+    let above = Vec3::new(0.0, 1.0 + PLAYER_COLLIDE_DISTANCE, 0.0);
+    let fall = Vec3::new(0.1, -0.1, 0.0);
+    assert!(push_direction((&body, &body_type, body_entity), above, fall).is_none());
+
+    // pushing never slows it down
+    rigid_body.push(Vec3::new(1.0, 0.0, 0.0), 0.0);
+    assert!(rigid_body.velocity[0] > 0.0);
+    rigid_body.push(Vec3::new(1.0, 0.0, 0.0), 2.0);
+    assert!((rigid_body.velocity[0] - 2.0).abs() < 1e-4);
+}
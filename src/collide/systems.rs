@@ -5,6 +5,7 @@ use ::specs::prelude::*;
 use crate::{
     collide::get_entities_in_bbox,
     components::*,
+    config::Config,
     ecs_utils::{Componentable, ModSystem, SystemName},
     input::{key_map::PRINT_DEBUG, Input},
     spatial_hash::{HashInt, SpatialHashSet},
@@ -12,8 +13,9 @@ use crate::{
 };
 
 use super::{
-    check_player_static_collisions, get_bbox_cells, get_dcoords_dcells, insert_static_bboxes,
-    move_player, update_player_bbox, update_static_bboxes,
+    check_body_static_collisions, check_player_static_collisions, get_bbox_cells,
+    get_dcoords_dcells, insert_static_bboxes, move_player, push_direction, swept_bbox,
+    update_player_bbox, update_static_bboxes, SWEEP_EPSILON,
 };
 
 pub struct MovePlayerSystem<V>(pub PhantomData<V>);
@@ -109,14 +111,22 @@ where
     type SystemData = (
         ReadExpect<'a, Player>,
         ReadStorage<'a, BBox<V>>,
+        ReadStorage<'a, MoveNext<V>>,
         WriteStorage<'a, InPlayerCell>,
         ReadExpect<'a, SpatialHashSet<V, Entity>>,
     );
 
-    fn run(&mut self, (player, bbox, mut in_cell, hash): Self::SystemData) {
-        in_cell.clear(); //clear previously marked
-                         //maybe we should use the anticipated player bbox here
-        let entities_in_bbox = get_entities_in_bbox(bbox.get(player.0).unwrap(), &hash);
+    fn run(&mut self, (player, bbox, move_next, mut in_cell, hash): Self::SystemData) {
+        //clear previously marked
+        in_cell.clear();
+        //mark everything along the player's next move, so fast moves can't skip over shapes
+        let bbox = swept_bbox(
+            bbox.get(player.0).unwrap(),
+            move_next
+                .get(player.0)
+                .and_then(|move_next| move_next.next_dpos),
+        );
+        let entities_in_bbox = get_entities_in_bbox(&bbox, &hash);
         for &e in entities_in_bbox.iter() {
             in_cell
                 .insert(e, InPlayerCell)
@@ -138,7 +148,9 @@ where
         ReadStorage<'a, ShapeType<V>>,
         ReadStorage<'a, StaticCollider>,
         ReadStorage<'a, InPlayerCell>,
+        Entities<'a>,
         WriteStorage<'a, MoveNext<V>>,
+        WriteStorage<'a, VerticalMotion>,
     );
 
     fn run(
@@ -150,16 +162,147 @@ where
             shape_types,
             static_collider,
             in_cell,
+            entities,
             mut write_move_next,
-        ) : Self::SystemData,
+            mut vertical_motions,
+        ): Self::SystemData,
     ) {
-        check_player_static_collisions(
+        let contacts = check_player_static_collisions(
             write_move_next.get_mut(player.0).unwrap(),
             transform.get(player.0).unwrap().pos,
-            (&shape, &shape_types, &static_collider, &in_cell)
+            (&shape, &shape_types, &static_collider, &in_cell, &entities)
                 .join()
-                .map(|(shape, shape_type, _, _)| (shape, shape_type)),
+                .map(|(shape, shape_type, _, _, e)| (shape, shape_type, e)),
+        );
+        if let Some(motion) = vertical_motions.get_mut(player.0) {
+            motion.land(&contacts);
+        }
+    }
+}
+
+// push the rigid bodies the player walks into, along the ground and away from the player
+pub struct PlayerPushSystem<V>(pub PhantomData<V>);
+impl<'a, V> System<'a> for PlayerPushSystem<V>
+where
+    V: VectorTrait + Componentable,
+    V::M: Componentable,
+{
+    type SystemData = (
+        ReadExpect<'a, Input>,
+        ReadExpect<'a, Player>,
+        ReadStorage<'a, Transform<V, V::M>>,
+        ReadStorage<'a, MoveNext<V>>,
+        ReadStorage<'a, Shape<V>>,
+        ReadStorage<'a, ShapeType<V>>,
+        ReadStorage<'a, InPlayerCell>,
+        Entities<'a>,
+        WriteStorage<'a, RigidBody<V>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            input,
+            player,
+            transforms,
+            move_next,
+            shapes,
+            shape_types,
+            in_cell,
+            entities,
+            mut rigid_bodies,
+        ): Self::SystemData,
+    ) {
+        let (
+            Some(MoveNext {
+                next_dpos: Some(dpos),
+                can_move: Some(true),
+            }),
+            Some(transform),
+        ) = (move_next.get(player.0), transforms.get(player.0))
+        else {
+            return;
+        };
+        let dt = input.get_dt();
+        for (shape, shape_type, _, e, rigid_body) in (
+            &shapes,
+            &shape_types,
+            &in_cell,
+            &entities,
+            &mut rigid_bodies,
         )
+            .join()
+        {
+            if let Some(direction) = push_direction((shape, shape_type, e), transform.pos, *dpos) {
+                rigid_body.push(direction, dpos.dot(direction) / dt);
+            }
+        }
+    }
+}
+
+// move the rigid bodies by their velocities, stopping and bouncing at static colliders.
+// modified transforms are picked up by TransformShapeSystem, which updates the rest of the shape
+pub struct RigidBodySystem<V>(pub PhantomData<V>);
+impl<'a, V> System<'a> for RigidBodySystem<V>
+where
+    V: VectorTrait + Componentable,
+    V::M: Componentable,
+{
+    type SystemData = (
+        ReadExpect<'a, Input>,
+        ReadExpect<'a, Config>,
+        ReadStorage<'a, Shape<V>>,
+        ReadStorage<'a, ShapeType<V>>,
+        ReadStorage<'a, StaticCollider>,
+        ReadStorage<'a, BBox<V>>,
+        ReadExpect<'a, SpatialHashSet<V, Entity>>,
+        Entities<'a>,
+        WriteStorage<'a, RigidBody<V>>,
+        WriteStorage<'a, Transform<V, V::M>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            input,
+            config,
+            shapes,
+            shape_types,
+            static_colliders,
+            bboxes,
+            hash,
+            entities,
+            mut rigid_bodies,
+            mut transforms,
+        ): Self::SystemData,
+    ) {
+        let dt = input.get_dt();
+        let mut moves = Vec::new();
+        for (shape, shape_type, bbox, e, rigid_body) in
+            (&shapes, &shape_types, &bboxes, &entities, &mut rigid_bodies).join()
+        {
+            rigid_body.accelerate(&config.physics, dt);
+            let dpos = rigid_body.velocity * dt;
+            let nearby = get_entities_in_bbox(&swept_bbox(bbox, Some(dpos)), &hash);
+            let (moved, normals) = check_body_static_collisions(
+                (shape, shape_type, e),
+                dpos,
+                nearby
+                    .into_iter()
+                    .filter(|&other| other != e && static_colliders.contains(other))
+                    .filter_map(|other| Some((shapes.get(other)?, shape_types.get(other)?, other))),
+            );
+            rigid_body.bounce(&normals, &config.physics, dt);
+            // bodies resting on the ground are left alone, so their shapes aren't updated
+            if moved.norm() > SWEEP_EPSILON {
+                moves.push((e, moved));
+            }
+        }
+        for (e, moved) in moves {
+            if let Some(transform) = transforms.get_mut(e) {
+                transform.translate(moved);
+            }
+        }
     }
 }
 
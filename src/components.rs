@@ -2,20 +2,27 @@ use specs::{Component, FlaggedStorage, HashMapStorage, VecStorage};
 
 use crate::coin::Coin;
 pub use crate::draw::draw_line_collection::DrawLineCollection;
+pub use crate::draw::shadow::DropShadow;
+pub use crate::draw::texture::library::TextureLabel;
 pub use crate::draw::ShapeTexture;
 use crate::ecs_utils::Componentable;
 //resources
 pub use crate::player::Player;
 //components
+pub use crate::animation::Animation;
 pub use crate::camera::Camera;
-pub use crate::collide::{bbox::BBox, bbox::HasBBox, InPlayerCell, MoveNext, StaticCollider};
+pub use crate::collide::{
+    bbox::BBox, bbox::HasBBox, rigid_body::RigidBody, InPlayerCell, MoveNext, StaticCollider,
+};
 pub use crate::draw::clipping::{bball::BBall, ClipState, ShapeClipState};
+pub use crate::draw::line_cache::ShapeLinesCache;
 pub use crate::draw::DrawLineList;
 pub use crate::geometry::{
     shape::RefShapes,
     shape::{Convex, Shape, ShapeLabel, ShapeType, ShapeTypeTrait, SingleFace},
     transform::{Transform, Transformable},
 };
+pub use crate::gravity::VerticalMotion;
 pub use crate::player::{Cursor, Heading, MaybeSelected, MaybeTarget, Selected};
 use crate::vector::VectorTrait;
 
@@ -40,11 +47,17 @@ impl<V: Componentable> Component for ShapeClipState<V> {
     type Storage = DefaultStorage<Self>;
 }
 impl<U: Componentable> Component for ShapeTexture<U> {
+    type Storage = FlaggedStorage<Self, DefaultStorage<Self>>;
+}
+impl<V: Componentable> Component for ShapeLinesCache<V> {
     type Storage = DefaultStorage<Self>;
 }
 impl<V: Componentable, M: Componentable> Component for Transform<V, M> {
     type Storage = FlaggedStorage<Self, DefaultStorage<Self>>;
 }
+impl<V: Componentable, M: Componentable> Component for Animation<V, M> {
+    type Storage = HashMapStorage<Self>;
+}
 impl<V: Componentable> Component for Camera<V> {
     type Storage = DefaultStorage<Self>;
 }
@@ -56,6 +69,9 @@ impl<V: Componentable> Component for MoveNext<V> {
     type Storage = DefaultStorage<Self>;
 }
 
+impl<V: Componentable> Component for RigidBody<V> {
+    type Storage = HashMapStorage<Self>;
+}
 impl<V: Componentable> Component for MaybeTarget<V> {
     type Storage = HashMapStorage<Self>;
 }
@@ -64,6 +80,10 @@ impl<V: Componentable> Component for DrawLineCollection<V> {
     type Storage = HashMapStorage<Self>;
 }
 
+impl<V: Componentable> Component for DropShadow<V> {
+    type Storage = HashMapStorage<Self>;
+}
+
 impl<M: Componentable> Component for Heading<M> {
     type Storage = HashMapStorage<Self>;
 }
@@ -2,15 +2,31 @@ use std::error;
 
 use serde::{Deserialize, Serialize};
 
-use crate::{constants::CONFIG_FILE_PATH_STR, draw::ViewportShape, vector::Field};
+use crate::{
+    constants::CONFIG_FILE_PATH_STR,
+    draw::{LineStyle, Projection, ViewportShape},
+    graphics::colors::{Color, Colormap, BLUE, ORANGE, WHITE, YELLOW},
+    vector::Field,
+};
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub struct ViewConfig {
     pub height: Field,
     pub radius: Field,
     pub viewport_shape: ViewportShape,
+    // draws the boundary of the viewport
+    pub viewport_outline: bool,
+    pub projection: Projection,
+    // projection used to draw the projected volume in 4d mode
+    pub volume_projection: Projection,
     pub focal: Field,
     pub spin_speed: Field,
+    pub depth_cue: DepthCueConfig,
+    pub hidden_axis: HiddenAxisConfig,
+    pub stereo: StereoConfig,
+    pub fill: FillConfig,
+    pub lines: LineConfig,
+    pub ghost: GhostConfig,
 }
 impl Default for ViewConfig {
     fn default() -> Self {
@@ -18,8 +34,190 @@ impl Default for ViewConfig {
             height: 0.5,
             radius: 0.6,
             viewport_shape: ViewportShape::Cylinder,
+            viewport_outline: true,
+            projection: Projection::Perspective,
+            volume_projection: Projection::Perspective,
             focal: 1.0,
             spin_speed: 0.1,
+            depth_cue: Default::default(),
+            hidden_axis: Default::default(),
+            stereo: Default::default(),
+            fill: Default::default(),
+            lines: Default::default(),
+            ghost: Default::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum DepthFalloff {
+    None,
+    Linear,
+    Exponential,
+}
+
+// fades lines with their distance from the camera. lines nearer than near are not faded.
+// linear falloff reaches min_factor at far; exponential falloff decays by a factor of e every 1 / density
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct DepthCueConfig {
+    pub falloff: DepthFalloff,
+    pub near: Field,
+    pub far: Field,
+    pub density: Field,
+    pub min_factor: Field,
+}
+impl Default for DepthCueConfig {
+    fn default() -> Self {
+        Self {
+            falloff: DepthFalloff::Exponential,
+            near: 2.0,
+            far: 20.0,
+            density: 0.1,
+            min_factor: 0.2,
+        }
+    }
+}
+impl DepthCueConfig {
+    pub fn factor(&self, distance: Field) -> Field {
+        let beyond_near = (distance - self.near).max(0.0);
+        let factor = match self.falloff {
+            DepthFalloff::None => return 1.0,
+            DepthFalloff::Linear => 1.0 - beyond_near / (self.far - self.near),
+            DepthFalloff::Exponential => (-self.density * beyond_near).exp(),
+        };
+        factor.clamp(self.min_factor, 1.0)
+    }
+}
+
+// colors line vertices by their view space depth along the axis discarded by projection.
+// depths from near to far span the colormap
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct HiddenAxisConfig {
+    pub enabled: bool,
+    pub colormap: Colormap,
+    pub near: Field,
+    pub far: Field,
+}
+impl Default for HiddenAxisConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            colormap: Colormap::Rainbow,
+            near: 1.0,
+            far: 15.0,
+        }
+    }
+}
+impl HiddenAxisConfig {
+    // keeps the alpha of the original color
+    pub fn color(&self, depth: Field, color: Color) -> Color {
+        self.colormap
+            .color((depth - self.near) / (self.far - self.near))
+            .set_alpha(color.get_arr()[3])
+    }
+}
+
+// fills visible faces with their texture color behind the lines. alpha scales the color's alpha.
+// faces are only filled in cube and plane viewports, or without a viewport
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct FillConfig {
+    pub enabled: bool,
+    pub alpha: f32,
+}
+impl Default for FillConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            alpha: 0.1,
+        }
+    }
+}
+
+// line widths are in screen units, for the 3d view and for the projected volume of the 4d view.
+// antialias smooths the edges of the lines in the shader
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct LineConfig {
+    pub width_3d: f32,
+    pub width_4d: f32,
+    pub antialias: bool,
+}
+impl Default for LineConfig {
+    fn default() -> Self {
+        Self {
+            width_3d: 0.01,
+            width_4d: 0.02,
+            antialias: true,
+        }
+    }
+}
+
+// draws the parts of lines hidden behind other shapes instead of dropping them.
+// hidden lines are drawn in style, with their alpha scaled by dim
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct GhostConfig {
+    pub enabled: bool,
+    pub dim: f32,
+    pub style: LineStyle,
+}
+impl Default for GhostConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dim: 0.3,
+            style: LineStyle::Dashed,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum StereoMode {
+    Off,
+    Anaglyph,
+    SideBySide,
+}
+
+// draws the projected volume (4d mode only) once per eye. the eyes are separated along the screen
+// horizontal and turned inwards to meet at the convergence distance along the view direction
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct StereoConfig {
+    pub mode: StereoMode,
+    pub eye_separation: f32,
+    pub convergence: f32,
+}
+impl Default for StereoConfig {
+    fn default() -> Self {
+        Self {
+            mode: StereoMode::Off,
+            eye_separation: 0.3,
+            convergence: 4.9,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum SplitViewMode {
+    Off,
+    // one orthographic view down each world axis
+    AxisViews,
+    // one orthographic view down the vertical axis
+    Map,
+}
+
+// extra views drawn over the main view, centered on the player.
+// view_radius is the distance from the player shown at the edge of each view,
+// and size is the fraction of the screen width taken up by the views
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct SplitViewConfig {
+    pub mode: SplitViewMode,
+    pub view_radius: Field,
+    pub size: f32,
+}
+impl Default for SplitViewConfig {
+    fn default() -> Self {
+        Self {
+            mode: SplitViewMode::Off,
+            view_radius: 20.0,
+            size: 0.25,
         }
     }
 }
@@ -27,18 +225,34 @@ impl Default for ViewConfig {
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct FuzzLinesConfig {
     pub face_num: usize,
-    pub sky_num: usize,
-    pub horizon_num: usize,
 }
 impl Default for FuzzLinesConfig {
+    fn default() -> Self {
+        Self { face_num: 500 }
+    }
+}
+
+// texture detail decreases linearly from full detail at distance near to the lowest detail at far
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct LodConfig {
+    pub enabled: bool,
+    pub near: Field,
+    pub far: Field,
+}
+impl Default for LodConfig {
     fn default() -> Self {
         Self {
-            face_num: 500,
-            sky_num: 1000,
-            horizon_num: 500,
+            enabled: true,
+            near: 4.0,
+            far: 24.0,
         }
     }
 }
+impl LodConfig {
+    pub fn detail(&self, distance: Field) -> Field {
+        ((self.far - distance) / (self.far - self.near)).clamp(0.0, 1.0)
+    }
+}
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum LevelConfig {
@@ -52,14 +266,106 @@ pub enum LevelConfig {
 pub struct Level1Config {
     pub open_center: bool,
 }
+// ground markers below the player and coins. see draw::shadow
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ShadowConfig {
+    pub player: bool,
+    pub coins: bool,
+    pub size: Field,
+}
+impl Default for ShadowConfig {
+    fn default() -> Self {
+        Self {
+            player: true,
+            coins: true,
+            size: 0.1,
+        }
+    }
+}
+
+// directional light for levels that don't have their own. see draw::light::Light
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct LightConfig {
+    pub enabled: bool,
+    // towards the light. extra components are ignored in lower dimensions
+    pub direction: Vec<Field>,
+    pub ambient: Field,
+}
+impl Default for LightConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            direction: vec![0.3, 1.0, 0.5, 0.2],
+            ambient: 0.5,
+        }
+    }
+}
+
+// marker in the sky towards the scene light
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct SunConfig {
+    pub enabled: bool,
+    pub color: Color,
+    // angular radius, in degrees
+    pub size: Field,
+}
+impl Default for SunConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            color: YELLOW,
+            size: 3.0,
+        }
+    }
+}
+
+// the sky drawn around the player. see draw::sky
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct SkyConfig {
+    // the sky fades from the horizon color to the zenith color with elevation
+    pub horizon_color: Color,
+    pub zenith_color: Color,
+    // number of rings of the gradient, evenly spaced up to the band elevation in degrees
+    pub bands: usize,
+    pub band_elevation: Field,
+    pub constellations: usize,
+    pub constellation_stars: usize,
+    // angular radius of a constellation, in degrees
+    pub constellation_size: Field,
+    pub star_color: Color,
+    // the same seed always gives the same constellations
+    pub seed: u64,
+    pub sun: SunConfig,
+}
+impl Default for SkyConfig {
+    fn default() -> Self {
+        Self {
+            horizon_color: ORANGE.set_alpha(0.5),
+            zenith_color: BLUE.set_alpha(0.5),
+            bands: 6,
+            band_elevation: 60.0,
+            constellations: 12,
+            constellation_stars: 6,
+            constellation_size: 12.0,
+            star_color: WHITE,
+            seed: 0,
+            sun: SunConfig::default(),
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct SceneConfig {
     pub grid: bool,
+    // the gradient bands above the horizon
     pub sky: bool,
     pub horizon: bool,
     pub stars: bool,
     pub level: LevelConfig,
     pub level_1: Option<Level1Config>,
+    pub sky_dome: SkyConfig,
+    pub light: LightConfig,
+    pub shadows: ShadowConfig,
 }
 impl Default for SceneConfig {
     fn default() -> Self {
@@ -70,6 +376,32 @@ impl Default for SceneConfig {
             stars: true,
             level: LevelConfig::Level1,
             level_1: None,
+            sky_dome: SkyConfig::default(),
+            light: LightConfig::default(),
+            shadows: ShadowConfig::default(),
+        }
+    }
+}
+
+// player movement under gravity. see gravity::VerticalMotion
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PhysicsConfig {
+    // without gravity, the player moves freely in every direction
+    pub fly: bool,
+    pub gravity: Field,
+    pub jump_speed: Field,
+    pub terminal_velocity: Field,
+    // how fast rigid bodies slow down while sliding along the ground
+    pub friction: Field,
+}
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            fly: false,
+            gravity: 9.8,
+            jump_speed: 3.0,
+            terminal_velocity: 5.0,
+            friction: 5.0,
         }
     }
 }
@@ -82,8 +414,11 @@ pub struct EditorConfig {
 #[derive(Clone, Serialize, Deserialize, Debug, Default)]
 pub struct Config {
     pub fuzz_lines: FuzzLinesConfig,
+    pub lod: LodConfig,
     pub view: ViewConfig,
+    pub split_view: SplitViewConfig,
     pub scene: SceneConfig,
+    pub physics: PhysicsConfig,
     pub editor: EditorConfig,
 }
 
@@ -14,10 +14,15 @@ pub const AXES_COLORS: [Color; 4] = [RED, GREEN, CYAN, MAGENTA];
 
 pub const SKY_DISTANCE: Field = 1e4;
 pub const STAR_SIZE: Field = 100.0;
-pub const SKY_FUZZ_SIZE: Field = 100.0;
 
 pub const SELECTION_COLOR: Color = WHITE.set_alpha(0.2);
+pub const VIEWPORT_COLOR: Color = GRAY.set_alpha(0.5);
+pub const DASH_LENGTH: Field = 0.04;
+pub const DOT_LENGTH: Field = 0.01;
+pub const LABEL_HEIGHT: Field = 0.2;
 pub const CURSOR_COLOR: Color = WHITE;
+pub const AXIS_VIEW_COLOR: Color = GRAY;
+pub const SHADOW_COLOR: Color = WHITE.set_alpha(0.5);
 
 pub const CUBE_LABEL_STR: &str = "Cube";
 pub const COIN_LABEL_STR: &str = "Coin";
@@ -28,15 +33,21 @@ pub const SMALL_Z: Field = 0.001;
 pub const Z_NEAR: Field = 0.1;
 
 pub const PLAYER_COLLIDE_DISTANCE: Field = 0.2;
+// faces whose normal is at least this close to vertical are floors or ceilings
+pub const GROUND_NORMAL_MIN: Field = 0.7;
 
 pub const SPEED: Field = 1.5;
 pub const ANG_SPEED: Field = 1.5 * PI / 3.0;
 pub const MAX_TILT: Field = 0.99;
 
 pub const MAX_TARGET_DIST: Field = 10.;
+pub const MAX_SHADOW_DIST: Field = 100.;
+pub const SHADOW_LIFT: Field = 0.01;
 
 pub const FRAME_MS: u64 = 16;
 
 pub const CONFIG_FILE_PATH_STR: &str = "./4d_config.toml";
 
 pub const FACE_SCALE: Field = 0.8;
+// width of the range of detail levels over which texture lines fade in or out
+pub const LOD_FADE: Field = 0.1;
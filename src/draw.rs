@@ -5,14 +5,18 @@ use rand::seq::IteratorRandom;
 use serde::__private::de;
 use serde::{Deserialize, Serialize};
 
-use clipping::{clip_line_cube, clip_line_plane, ClipState};
-use specs::rayon::iter::Chain;
-use specs::{Join, ReadStorage};
+use clipping::{
+    clip_line_cube, clip_line_plane, clip_line_planes, clip_line_superellipsoid, ClipState,
+};
+use specs::rayon::iter::{Chain, ParallelIterator};
+use specs::{Join, ParJoin, ReadStorage, WriteStorage};
 pub use texture::{FaceTexture, ShapeTexture, Texture, TextureMapping};
 
 use crate::components::*;
-use crate::config::ViewConfig;
-use crate::constants::{CURSOR_COLOR, SELECTION_COLOR, SMALL_Z, Z0, Z_NEAR};
+use crate::config::{GhostConfig, ViewConfig};
+use crate::constants::{
+    CURSOR_COLOR, DASH_LENGTH, DOT_LENGTH, SELECTION_COLOR, SMALL_Z, Z0, Z_NEAR,
+};
 use crate::ecs_utils::Componentable;
 use crate::geometry::Face;
 use crate::geometry::{shape::VertIndex, Line, Shape};
@@ -20,26 +24,62 @@ use crate::graphics::colors::*;
 use crate::vector::{barycenter, linspace, Field, VecIndex, VectorTrait};
 
 use self::clipping::{clip_line_cylinder, clip_line_sphere, clip_line_tube};
-use self::texture::draw_face_texture;
+use self::line_cache::ShapeLinesCache;
+use self::texture::{draw_face_texture, lines_at_detail};
+use self::viewport::{superellipsoid_radii, viewport_planes, ViewportPlane};
 use self::visual_aids::{calc_wireframe_lines, draw_axes};
 
 pub mod clipping;
 pub mod draw_line_collection;
+pub mod fill;
+pub mod light;
+pub mod line_cache;
+pub mod shadow;
+pub mod sky;
+pub mod split_view;
 pub mod systems;
 pub mod texture;
+pub mod viewport;
 pub mod visual_aids;
 
 extern crate map_in_place;
 
-#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub enum ViewportShape {
     Cube,
     Sphere,
     Cylinder,
     Tube,
+    // the volume inside all of the planes
+    Planes(Vec<ViewportPlane>),
+    // sum |x_i / r_i|^exponent <= 1. an exponent of 2 gives an ellipsoid, and it approaches a box
+    // as the exponent grows. missing radii are the view radius
+    Superellipsoid { radii: Vec<Field>, exponent: Field },
     None,
 }
 
+// orthographic projection scales by focal without dividing by depth.
+// oblique projection is orthographic, sheared by depth times depth_scale
+// in the direction angle (radians from the first screen axis towards the second).
+// depth_scale is 1 for a cavalier projection and 0.5 for a cabinet projection
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum Projection {
+    Perspective,
+    Orthographic,
+    Oblique { angle: Field, depth_scale: Field },
+}
+impl Projection {
+    // screen space shift per unit depth
+    fn shear<U: VectorTrait>(&self) -> U {
+        match *self {
+            Projection::Oblique { angle, depth_scale } => {
+                (U::one_hot(0) * angle.cos() + U::one_hot(1) * angle.sin()) * depth_scale
+            }
+            _ => U::zero(),
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct DrawVertex<V>
 where
@@ -49,10 +89,37 @@ where
     pub color: Color,
 }
 
+// how a line is stroked. dashes are measured along the projected line, and restart
+// wherever the line is clipped
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize, Debug)]
+pub enum LineStyle {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+impl LineStyle {
+    // lengths of each dash and of the period of the pattern. solid lines have no pattern
+    pub fn dash_pattern(self) -> Option<[Field; 2]> {
+        match self {
+            LineStyle::Solid => None,
+            LineStyle::Dashed => Some([DASH_LENGTH, DASH_LENGTH * 1.75]),
+            LineStyle::Dotted => Some([DOT_LENGTH, DOT_LENGTH * 3.0]),
+        }
+    }
+    pub fn is_drawn_at(self, distance: Field) -> bool {
+        self.dash_pattern()
+            .is_none_or(|[dash, period]| distance.rem_euclid(period) < dash)
+    }
+}
+
 #[derive(Clone)]
 pub struct DrawLine<V> {
     pub line: Line<V>,
     pub color: Color,
+    // color at the end of the line, if it differs from the start. blended along the line when drawn
+    pub end_color: Option<Color>,
+    pub style: LineStyle,
 }
 impl<V: VectorTrait> DrawLine<V> {
     pub fn map_line<F, U>(self, f: F) -> DrawLine<U>
@@ -63,6 +130,8 @@ impl<V: VectorTrait> DrawLine<V> {
         DrawLine {
             line: f(self.line),
             color: self.color,
+            end_color: self.end_color,
+            style: self.style,
         }
     }
     pub fn get_draw_verts(&self) -> [DrawVertex<V>; 2] {
@@ -73,26 +142,62 @@ impl<V: VectorTrait> DrawLine<V> {
             },
             DrawVertex {
                 vertex: self.line.1,
-                color: self.color,
+                color: self.end_color.unwrap_or(self.color),
             },
         ]
     }
 }
 
-fn project<V>(focal: Field, v: V) -> V::SubV
+fn project<V>(projection: Projection, focal: Field, v: V) -> V::SubV
 where
     V: VectorTrait,
 {
-    let z = if V::is_close(v, V::ones() * Z0) {
-        Z0 + SMALL_Z
-    } else {
-        v[-1]
-    };
-    v.project() * focal / z
+    match projection {
+        Projection::Perspective => {
+            let z = if V::is_close(v, V::ones() * Z0) {
+                Z0 + SMALL_Z
+            } else {
+                v[-1]
+            };
+            v.project() * focal / z
+        }
+        Projection::Orthographic | Projection::Oblique { .. } => {
+            (v.project() + projection.shear::<V::SubV>() * v[-1]) * focal
+        }
+    }
 }
 fn view_transform<V: VectorTrait>(transform: &Transform<V, V::M>, point: V) -> V {
     transform.frame * (point - transform.pos)
 }
+// depth along the projection axis of a point on a projected line.
+// in perspective, the reciprocal of the depth varies linearly along the projected line
+fn projected_depth<U: VectorTrait>(
+    projection: Projection,
+    point: U,
+    projected_line: &Line<U>,
+    depths: &Line<Field>,
+) -> Field {
+    let dir = projected_line.1 - projected_line.0;
+    let len_sq = dir.dot(dir);
+    let t = if len_sq > 0.0 {
+        ((point - projected_line.0).dot(dir) / len_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    match projection {
+        Projection::Perspective => 1.0 / Line(1.0 / depths.0, 1.0 / depths.1).linterp(t),
+        Projection::Orthographic | Projection::Oblique { .. } => depths.linterp(t),
+    }
+}
+
+pub struct ProjectedLine<U> {
+    pub line: Line<U>,
+    // view space distance to the middle of the line (before projection), for depth cueing
+    pub distance: Field,
+    // view space depth discarded by projection at each end of the line
+    pub depths: Line<Field>,
+}
+
 //can likely remove camera here by calculating the plane from the transform, unless you want the
 //camera's plane to differ from its position/heading
 pub fn transform_line<V: VectorTrait>(
@@ -100,23 +205,130 @@ pub fn transform_line<V: VectorTrait>(
     transform: &Transform<V, V::M>,
     camera: &Camera<V>,
     view_config: &ViewConfig,
-) -> Option<Line<V::SubV>>
+) -> Option<ProjectedLine<V::SubV>>
 where
     V: VectorTrait,
 {
     let r = view_config.radius;
     let h = view_config.height;
-    clip_line_plane(line, &camera.plane, Z_NEAR)
-        .map(|l| l.map(|v| project(view_config.focal, view_transform(transform, v))))
-        .and_then(|l| match view_config.viewport_shape {
-            ViewportShape::Cube => clip_line_cube(l, r),
-            ViewportShape::Sphere => clip_line_sphere(l, r),
-            ViewportShape::Cylinder => clip_line_cylinder(l, r, h),
-            ViewportShape::Tube => clip_line_tube(l, r),
-            ViewportShape::None => Some(l),
+    let view_line =
+        clip_line_plane(line, &camera.plane, Z_NEAR)?.map(|v| view_transform(transform, v));
+    let distance = view_line.linterp(0.5).norm();
+    let view_depths = view_line.map(|v| v[-1]);
+    let projection = view_config.projection;
+    let projected_line = view_line.map(|v| project(projection, view_config.focal, v));
+    match &view_config.viewport_shape {
+        ViewportShape::Cube => clip_line_cube(projected_line.clone(), r),
+        ViewportShape::Sphere => clip_line_sphere(projected_line.clone(), r),
+        ViewportShape::Cylinder => clip_line_cylinder(projected_line.clone(), r, h),
+        ViewportShape::Tube => clip_line_tube(projected_line.clone(), r),
+        ViewportShape::Planes(planes) => {
+            clip_line_planes(projected_line.clone(), &viewport_planes(planes))
+        }
+        ViewportShape::Superellipsoid { radii, exponent } => clip_line_superellipsoid(
+            projected_line.clone(),
+            superellipsoid_radii(radii, r),
+            *exponent,
+        ),
+        ViewportShape::None => Some(projected_line.clone()),
+    }
+    .map(|line| ProjectedLine {
+        depths: line.map(|p| projected_depth(projection, p, &projected_line, &view_depths)),
+        line,
+        distance,
+    })
+}
+
+/// whether a bounding ball is entirely behind the camera or projects entirely outside the viewport.
+/// the projected ball is bounded by a box along the screen axes, so this is conservative
+pub fn bball_culled<V: VectorTrait>(
+    bball: &BBall<V>,
+    transform: &Transform<V, V::M>,
+    camera: &Camera<V>,
+    view_config: &ViewConfig,
+) -> bool {
+    let r = bball.radius;
+    let z = camera.plane.point_signed_distance(bball.pos);
+    if z + r < Z_NEAR {
+        return true;
+    }
+    let (projection, focal) = (view_config.projection, view_config.focal);
+    let view_center = view_transform(transform, bball.pos);
+    // the projected ball is within focal_r of center along each axis, before dividing by depth
+    let (center, focal_r, z_min, z_max) = match projection {
+        Projection::Perspective => (
+            view_center.project() * focal,
+            r * focal,
+            Field::max(z - r, Z_NEAR),
+            z + r,
+        ),
+        Projection::Orthographic | Projection::Oblique { .. } => (
+            project(projection, focal, view_center),
+            r * (1.0 + projection.shear::<V::SubV>().norm()) * focal,
+            1.0,
+            1.0,
+        ),
+    };
+    let bounds: Vec<(Field, Field)> = center
+        .iter()
+        .map(|&c| {
+            let (lo, hi) = (c - focal_r, c + focal_r);
+            (
+                lo / if lo < 0.0 { z_min } else { z_max },
+                hi / if hi > 0.0 { z_min } else { z_max },
+            )
         })
+        .collect();
+
+    let (vr, h) = (view_config.radius, view_config.height);
+    let long_axis = 1;
+    let outside_interval =
+        |(lo, hi): (Field, Field), half_width: Field| lo > half_width || hi < -half_width;
+    let outside_ball = |axes: &mut dyn Iterator<Item = &(Field, Field)>| {
+        axes.map(|&(lo, hi)| Field::max(Field::max(lo, -hi), 0.0).powi(2))
+            .sum::<Field>()
+            > vr * vr
+    };
+    let mut radial_bounds = bounds
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != long_axis)
+        .map(|(_, b)| b);
+    match &view_config.viewport_shape {
+        ViewportShape::Cube => bounds.iter().any(|&b| outside_interval(b, vr)),
+        ViewportShape::Sphere => outside_ball(&mut bounds.iter()),
+        ViewportShape::Cylinder => {
+            outside_interval(bounds[long_axis], h) || outside_ball(&mut radial_bounds)
+        }
+        ViewportShape::Tube => outside_ball(&mut radial_bounds),
+        // the box is outside a plane if its nearest corner is
+        ViewportShape::Planes(planes) => viewport_planes::<V::SubV>(planes).iter().any(|plane| {
+            bounds
+                .iter()
+                .enumerate()
+                .map(|(i, &(lo, hi))| {
+                    let n = plane.normal[i as VecIndex];
+                    n * if n > 0.0 { lo } else { hi }
+                })
+                .sum::<Field>()
+                > plane.threshold
+        }),
+        // the superellipsoid lies within the box of its radii
+        ViewportShape::Superellipsoid { radii, .. } => {
+            let radii: V::SubV = superellipsoid_radii(radii, vr);
+            bounds
+                .iter()
+                .enumerate()
+                .any(|(i, &b)| outside_interval(b, radii[i as VecIndex]))
+        }
+        ViewportShape::None => false,
+    }
 }
 
+/// number of shapes culled in the last frame
+#[derive(Default)]
+pub struct ShapesCulled(pub usize);
+
 #[derive(Default)]
 pub struct DrawLineList<V>(pub Vec<DrawLine<V>>);
 impl<V: VectorTrait> DrawLineList<V> {
@@ -146,10 +358,32 @@ pub fn transform_draw_line<V: VectorTrait>(
     camera: &Camera<V>,
     view_config: &ViewConfig,
 ) -> Option<DrawLine<V::SubV>> {
-    transform_line(draw_line.line, transform, camera, view_config).map(|line| DrawLine {
-        line,
-        color: draw_line.color,
-    })
+    transform_line(draw_line.line, transform, camera, view_config).map(
+        |ProjectedLine {
+             line,
+             distance,
+             depths,
+         }| {
+            let hidden_axis = &view_config.hidden_axis;
+            let (color, end_color) = if hidden_axis.enabled {
+                let end_color = draw_line.end_color.unwrap_or(draw_line.color);
+                (
+                    hidden_axis.color(depths.0, draw_line.color),
+                    Some(hidden_axis.color(depths.1, end_color)),
+                )
+            } else {
+                (draw_line.color, draw_line.end_color)
+            };
+            let factor = view_config.depth_cue.factor(distance);
+            let fade = |color: Color| color.set_alpha(color.get_arr()[3] * factor);
+            DrawLine {
+                line,
+                color: fade(color),
+                end_color: end_color.map(fade),
+                style: draw_line.style,
+            }
+        },
+    )
 }
 
 pub fn draw_cursor<U: VectorTrait>(shape: &Shape<U>) -> impl Iterator<Item = DrawLine<U>> {
@@ -158,6 +392,8 @@ pub fn draw_cursor<U: VectorTrait>(shape: &Shape<U>) -> impl Iterator<Item = Dra
         .map(|line| DrawLine {
             line,
             color: CURSOR_COLOR,
+            end_color: None,
+            style: LineStyle::Solid,
         })
 }
 
@@ -197,8 +433,9 @@ pub fn update_shape_visibility<V: VectorTrait>(
         }
     }
 
-    //calculate boundaries for clipping
-    if clip_state.clipping_enabled {
+    //calculate boundaries for clipping. culled shapes don't clip, but targeting still reads their
+    //face visibility
+    if clip_state.clipping_enabled && !shape_clip_state.culled {
         shape_clip_state.boundaries = match shape_type {
             ShapeType::Convex(convex) => {
                 convex.calc_boundaries(camera_pos, &shape.faces, &shape_clip_state.face_visibility)
@@ -221,8 +458,10 @@ pub fn calc_shapes_lines<V>(
     shapes: &ReadStorage<Shape<V>>,
     shape_textures: &ReadStorage<ShapeTexture<V::SubV>>,
     shape_clip_states: &ReadStorage<ShapeClipState<V>>,
+    shape_lines_caches: &mut WriteStorage<ShapeLinesCache<V>>,
     face_scale: &[Field],
     clip_state: &ClipState<V>,
+    ghost: Option<&GhostConfig>,
 ) -> Vec<DrawLine<V>>
 where
     V: VectorTrait + Componentable,
@@ -235,47 +474,180 @@ where
     // 	println!("{}",s.in_front_debug());
     // }
     // panic!();
-    //probably worth computing / storing number of lines
-    //and using Vec::with_capacity
-    let mut lines: Vec<DrawLine<V>> = Vec::new();
 
-    //compute lines for each shape
-    for (shape, shape_texture, shape_clip_state) in
-        (shapes, shape_textures, shape_clip_states).join()
-    {
-        let mut shape_lines: Vec<DrawLine<V>> = Vec::new();
-        //get lines from each face
-        for (face, &visible, face_texture) in izip!(
-            shape.faces.iter(),
-            shape_clip_state.face_visibility.iter(),
-            shape_texture.face_textures.iter()
-        ) {
-            shape_lines.append(&mut draw_face_texture::<V>(
-                face_texture,
-                face,
-                shape,
-                face_scale,
-                visible,
-            ))
-        }
+    //compute lines for each shape in parallel. collecting into a vec preserves the join order
+    (
+        shapes,
+        shape_textures,
+        shape_clip_states,
+        shape_lines_caches,
+    )
+        .par_join()
+        .filter(|(_, _, shape_clip_state, _)| !shape_clip_state.culled)
+        .flat_map_iter(
+            |(shape, shape_texture, shape_clip_state, shape_lines_cache)| {
+                calc_shape_lines(
+                    shape,
+                    shape_texture,
+                    shape_clip_state,
+                    shape_lines_cache,
+                    clip_state.clipping_enabled.then_some(shape_clip_states),
+                    face_scale,
+                    ghost,
+                )
+            },
+        )
+        .collect()
+}
 
-        //clip these lines and append to list
-        if clip_state.clipping_enabled {
-            let clip_states_in_front =
-                shape_clip_state
-                    .in_front
-                    .iter()
-                    .map(|&e| match shape_clip_states.get(e) {
-                        Some(s) => s,
-                        None => panic!("Invalid entity {} found in shape_clip_state", e.id()),
-                    });
-            //do clipping between all shapes
-            //let shapes_in_front = shapes.join().filter(|&s| (s as *const _ ) != (shape as *const _));
-            let mut clipped_lines = clipping::clip_draw_lines(shape_lines, clip_states_in_front);
-            lines.append(&mut clipped_lines);
+fn calc_shape_lines<V>(
+    shape: &Shape<V>,
+    shape_texture: &ShapeTexture<V::SubV>,
+    shape_clip_state: &ShapeClipState<V>,
+    shape_lines_cache: &mut ShapeLinesCache<V>,
+    // None if clipping is disabled
+    shape_clip_states: Option<&ReadStorage<ShapeClipState<V>>>,
+    face_scale: &[Field],
+    ghost: Option<&GhostConfig>,
+) -> Vec<DrawLine<V>>
+where
+    V: VectorTrait + Componentable,
+    V::SubV: Componentable,
+{
+    let mut shape_lines: Vec<DrawLine<V>> = Vec::new();
+    //get lines from each visible face, reusing the lines from previous frames where possible
+    for (face_i, (face, &visible, face_texture)) in izip!(
+        shape.faces.iter(),
+        shape_clip_state.face_visibility.iter(),
+        shape_texture.face_textures.iter()
+    )
+    .enumerate()
+    {
+        if visible {
+            let face_lines = shape_lines_cache.get_or_insert_with(face_i, || {
+                draw_face_texture::<V>(face_texture, face, shape, face_scale, visible)
+            });
+            let lines_start = shape_lines.len();
+            match (shape_clip_state.detail, face_texture.texture.detail()) {
+                (Some(detail), Some(detail_ranges)) => {
+                    shape_lines.extend(lines_at_detail(face_lines, detail_ranges, detail))
+                }
+                _ => shape_lines.extend(face_lines.iter().cloned()),
+            }
+            // shading is applied after the cache, since it changes as the shape turns
+            if let Some(&brightness) = shape_clip_state.face_brightness.get(face_i) {
+                for draw_line in &mut shape_lines[lines_start..] {
+                    draw_line.color = draw_line.color.shade(brightness);
+                    draw_line.end_color = draw_line.end_color.map(|c| c.shade(brightness));
+                }
+            }
         } else {
-            lines.append(&mut shape_lines);
+            shape_lines_cache.remove(face_i)
         }
     }
-    lines
+
+    //clip these lines
+    if let Some(shape_clip_states) = shape_clip_states {
+        let clip_states_in_front =
+            shape_clip_state
+                .in_front
+                .iter()
+                .map(|&e| match shape_clip_states.get(e) {
+                    Some(s) => s,
+                    None => panic!("Invalid entity {} found in shape_clip_state", e.id()),
+                });
+        //do clipping between all shapes
+        //let shapes_in_front = shapes.join().filter(|&s| (s as *const _ ) != (shape as *const _));
+        clipping::clip_draw_lines(shape_lines, clip_states_in_front, ghost)
+    } else {
+        shape_lines
+    }
+}
+
+#[test]
+fn test_projected_depth() {
+    use crate::vector::{Vec2, Vec3};
+    let view_line = Line(Vec3::new(0.0, 0.0, 2.0), Vec3::new(2.0, 1.0, 6.0));
+    let depths = view_line.map(|v| v[-1]);
+    let projected_line = view_line.map(|v| project(Projection::Perspective, 1.0, v));
+    let mid_depth = projected_depth(
+        Projection::Perspective,
+        project(Projection::Perspective, 1.0, view_line.linterp(0.5)),
+        &projected_line,
+        &depths,
+    );
+    assert!((mid_depth - 4.0).abs() < 1e-5);
+    assert!(
+        (projected_depth(
+            Projection::Perspective,
+            projected_line.1,
+            &projected_line,
+            &depths
+        ) - 6.0)
+            .abs()
+            < 1e-5
+    );
+    assert!(
+        (projected_depth(
+            Projection::Perspective,
+            Vec2::zero(),
+            &projected_line,
+            &depths
+        ) - 2.0)
+            .abs()
+            < 1e-5
+    );
+}
+
+#[test]
+fn test_oblique_projection() {
+    use crate::vector::{Vec2, Vec3};
+    let v = Vec3::new(1.0, 2.0, 4.0);
+    assert!(Vec2::is_close(
+        project(Projection::Orthographic, 0.5, v),
+        Vec2::new(0.5, 1.0)
+    ));
+    let cabinet = Projection::Oblique {
+        angle: 0.0,
+        depth_scale: 0.5,
+    };
+    assert!(Vec2::is_close(
+        project(cabinet, 0.5, v),
+        Vec2::new(1.5, 1.0)
+    ));
+}
+
+#[test]
+fn test_bball_culled() {
+    use crate::vector::{Mat3, Vec3};
+    let transform = Transform::<Vec3, Mat3>::identity();
+    let camera = Camera::new(&transform);
+    let mut view_config = ViewConfig {
+        viewport_shape: ViewportShape::Cube,
+        ..Default::default()
+    };
+    let culled = |pos: Vec3, radius: Field, view_config: &ViewConfig| {
+        bball_culled(&BBall { pos, radius }, &transform, &camera, view_config)
+    };
+    // in perspective, balls ahead are kept, even when they reach behind the camera
+    assert!(!culled(Vec3::new(0.0, 0.0, 3.0), 0.5, &view_config));
+    assert!(!culled(Vec3::new(0.0, 0.0, 0.0), 0.5, &view_config));
+    assert!(culled(Vec3::new(0.0, 0.0, -3.0), 0.5, &view_config));
+    // off to the side, outside the viewport
+    assert!(culled(Vec3::new(5.0, 0.0, 3.0), 0.5, &view_config));
+    // far away, perspective shrinks the ball towards the center
+    assert!(!culled(Vec3::new(2.0, 0.0, 20.0), 0.5, &view_config));
+
+    // orthographic projection doesn't shrink it
+    view_config.projection = Projection::Orthographic;
+    assert!(culled(Vec3::new(2.0, 0.0, 20.0), 0.5, &view_config));
+    assert!(culled(Vec3::new(-1.5, 0.0, 3.0), 0.2, &view_config));
+    assert!(!culled(Vec3::new(0.0, 0.0, 20.0), 0.5, &view_config));
+    // oblique projection shifts the ball by its depth
+    view_config.projection = Projection::Oblique {
+        angle: 0.0,
+        depth_scale: 0.5,
+    };
+    assert!(!culled(Vec3::new(-1.5, 0.0, 3.0), 0.2, &view_config));
+    assert!(culled(Vec3::new(0.0, 0.0, 3.0), 0.2, &view_config));
 }
@@ -1,18 +1,22 @@
 pub mod bball;
+pub mod bsp;
 
 use crate::ecs_utils::Componentable;
 use crate::player::Player;
 use crate::vector::{Field, VecIndex, VectorTrait};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 use crate::components::{Shape, Transform};
+use crate::config::GhostConfig;
 use crate::draw::DrawLine;
 use crate::geometry::{sphere_line_intersect, sphere_t_intersect_infinite_normed, Line, Plane};
+use crate::graphics::colors::{blend, Color};
 
 use specs::{Component, Entities, Entity, Join, ReadStorage, VecStorage, WriteStorage};
 use std::marker::PhantomData;
 
 use self::bball::BBall;
+use self::bsp::BspTree;
 
 pub struct ClipState<V> {
     //pub in_front : Vec<Vec<bool>>,
@@ -59,21 +63,31 @@ impl<V> ClipState<V> {
     // }
 }
 pub struct ShapeClipState<V> {
-    pub in_front: HashSet<Entity>,
+    /// shapes that may occlude this one. static shapes come first, ordered front to back
+    pub in_front: Vec<Entity>,
     pub separators: HashMap<Entity, Separator<V>>,
     pub boundaries: Vec<Plane<V>>,
     pub transparent: bool,
+    /// set when the shape is entirely out of view. culled shapes are not drawn and do not clip
+    pub culled: bool,
+    /// level of detail of the shape's textures, between 0 and 1. None if LOD is disabled
+    pub detail: Option<Field>,
     pub face_visibility: Vec<bool>,
+    /// brightness of each face under the scene light
+    pub face_brightness: Vec<Field>,
 }
 
 impl<V: VectorTrait> Default for ShapeClipState<V> {
     fn default() -> Self {
         Self {
-            in_front: HashSet::new(),
+            in_front: Vec::new(),
             separators: HashMap::new(),
             boundaries: Vec::new(),
             transparent: false,
+            culled: false,
+            detail: None,
             face_visibility: Vec::new(),
+            face_brightness: Vec::new(),
         }
     }
 }
@@ -98,7 +112,7 @@ impl<V: VectorTrait + Componentable> ShapeClipState<V> {
 }
 impl<V: VectorTrait> ShapeClipState<V> {
     pub fn remove(&mut self, e: &Entity) {
-        self.in_front.remove(e);
+        self.in_front.retain(|f| f != e);
         self.separators.remove(e);
     }
 }
@@ -109,90 +123,97 @@ pub struct InFrontArg<'a, V> {
     pub entity: Entity,
 }
 
-//i've avoiding double mutable borrowing here by passing the entire shape_clip_states to calc_in_front_pair
-//a disadvantage here is that we have no guarantee that the processed entities have the ShapeClipState component
-//and that we have to iterate over all entities with the Shape component, instead of just those with both Shape and ShapeClipState
-//but for now, every shape has a ShapeClipState.
+/// find the occluders of every shape that has not been culled.
+/// occluders among the static shapes come from the BSP tree, so only the dynamic
+/// shapes (those not in the tree) are tested against every other shape.
 pub fn calc_in_front<V: VectorTrait + Componentable>(
     read_shapes: &ReadStorage<Shape<V>>,
     read_bballs: &ReadStorage<BBall<V>>,
     shape_clip_states: &mut WriteStorage<ShapeClipState<V>>,
     entities: &Entities,
+    bsp_tree: &BspTree<V, Entity>,
     origin: &V,
 ) {
-    //collect a vec of references to shapes
-    //let shapes : Vec<&Shape<V>> = (& read_shapes).join().collect();
-    //loop over unique pairs
-    for (shape1, bball1, e1) in (read_shapes, read_bballs, entities).join() {
-        for (shape2, bball2, e2) in (read_shapes, read_bballs, entities)
-            .join()
-            .filter(|(_sh, _bb, e)| *e > e1)
-        {
-            calc_in_front_pair(
-                InFrontArg {
-                    shape: shape1,
-                    bball: bball1,
-                    entity: e1,
-                },
-                InFrontArg {
-                    shape: shape2,
-                    bball: bball2,
-                    entity: e2,
-                },
-                shape_clip_states,
-                origin,
-            )
-        }
+    let all: Vec<Entity> = (read_shapes, read_bballs, &*shape_clip_states, entities)
+        .join()
+        .filter(|(_, _, clip_state, _)| !clip_state.culled)
+        .map(|(_, _, _, e)| e)
+        .collect();
+    let dynamic: Vec<Entity> = all
+        .iter()
+        .copied()
+        .filter(|e| !bsp_tree.contains(e))
+        .collect();
+    let arg = |entity: Entity| InFrontArg {
+        shape: read_shapes.get(entity).unwrap(),
+        bball: read_bballs.get(entity).unwrap(),
+        entity,
+    };
+    for &e in all.iter() {
+        let this = arg(e);
+        let mut test = |other: Entity| {
+            !shape_clip_states.get(other).unwrap().culled
+                && may_occlude(arg(other), this, shape_clip_states, origin)
+        };
+        let mut in_front = match bsp_tree.contains(&e) {
+            true => bsp_tree.occluders_of_item(&e, origin, &mut test),
+            false => bsp_tree.occluders(&this.shape.verts, this.bball, None, origin, &mut test),
+        };
+        in_front.extend(dynamic.iter().copied().filter(|&d| d != e && test(d)));
+        shape_clip_states.get_mut(e).unwrap().in_front = in_front;
     }
 }
 
-pub fn calc_in_front_pair<'a, V: VectorTrait + Componentable>(
+//i've avoiding double mutable borrowing here by passing the entire shape_clip_states to calc_separation
+//a disadvantage here is that we have no guarantee that the processed entities have the ShapeClipState component
+//but for now, every shape has a ShapeClipState.
+pub fn calc_separation<'a, V: VectorTrait + Componentable>(
     a: InFrontArg<'a, V>,
     b: InFrontArg<'a, V>,
     shape_clip_states: &mut WriteStorage<ShapeClipState<V>>,
     origin: &V,
-) {
+) -> Separation {
     //try dynamic separation
-    let mut sep_state = dynamic_separate(a.bball, b.bball, origin);
-    let is_unknown = matches!(sep_state, Separation::Unknown);
+    let sep_state = dynamic_separate(a.bball, b.bball, origin);
+    if !matches!(sep_state, Separation::Unknown) {
+        return sep_state;
+    }
     //if that's unsuccessful, try static separation
-    if is_unknown {
-        let a_clip_state = shape_clip_states.get_mut(a.entity).unwrap();
-        //let mut a_separators = shape_clip_states.get_mut(a.entity).unwrap().separators;
-        let maybe_sep = a_clip_state.separators.get_mut(&b.entity);
-
-        //compute static separator if it hasn't been computed yet
-        let sep = match maybe_sep {
-            Some(s) => *s,
-            None => {
-                let s = separate_between_centers(a, b);
-                a_clip_state.separators.insert(b.entity, s);
-                s
-            }
-        };
-
-        //determine separation state from separator
-        sep_state = sep.apply(origin);
-    };
-    let new_vals = match sep_state {
-        Separation::S1Front => (true, false),
-        Separation::S2Front => (false, true),
-        Separation::NoFront => (false, false),
-        Separation::Unknown => (true, true),
+    let a_clip_state = shape_clip_states.get_mut(a.entity).unwrap();
+    let maybe_sep = a_clip_state.separators.get_mut(&b.entity);
+
+    //compute static separator if it hasn't been computed yet
+    let sep = match maybe_sep {
+        Some(s) => *s,
+        None => {
+            let s = separate_between_centers(a, b);
+            a_clip_state.separators.insert(b.entity, s);
+            s
+        }
     };
-    {
-        let a_clip_state = shape_clip_states.get_mut(a.entity).unwrap();
-        match new_vals.0 {
-            true => a_clip_state.in_front.insert(b.entity),
-            false => a_clip_state.in_front.remove(&b.entity),
-        };
-    }
-    {
-        let b_clip_state = shape_clip_states.get_mut(b.entity).unwrap();
-        match new_vals.1 {
-            true => b_clip_state.in_front.insert(a.entity),
-            false => b_clip_state.in_front.remove(&a.entity),
-        };
+
+    //determine separation state from separator
+    sep.apply(origin)
+}
+
+/// whether the occluder may be in front of the shape.
+/// static separators are cached in the clip state of the lower entity of the pair
+pub fn may_occlude<'a, V: VectorTrait + Componentable>(
+    occluder: InFrontArg<'a, V>,
+    shape: InFrontArg<'a, V>,
+    shape_clip_states: &mut WriteStorage<ShapeClipState<V>>,
+    origin: &V,
+) -> bool {
+    if occluder.entity < shape.entity {
+        matches!(
+            calc_separation(occluder, shape, shape_clip_states, origin),
+            Separation::S2Front | Separation::Unknown
+        )
+    } else {
+        matches!(
+            calc_separation(shape, occluder, shape_clip_states, origin),
+            Separation::S1Front | Separation::Unknown
+        )
     }
 }
 
@@ -225,6 +246,27 @@ where
     }
 }
 
+// clips a convex polygon to the side of the plane that clip_line_plane keeps
+pub fn clip_polygon_plane<V>(polygon: Vec<V>, plane: &Plane<V>, small_z: Field) -> Vec<V>
+where
+    V: VectorTrait,
+{
+    let n = plane.normal;
+    let th = plane.threshold + small_z;
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+    for (&p0, &p1) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+        let (p0n, p1n) = (p0.dot(n), p1.dot(n));
+        let (p0_safe, p1_safe) = (p0n >= th, p1n >= th);
+        if p0_safe {
+            clipped.push(p0);
+        }
+        if p0_safe != p1_safe {
+            clipped.push(V::linterp(p0, p1, (p0n - th) / (p0n - p1n)));
+        }
+    }
+    clipped
+}
+
 pub fn clip_line_cube<V: VectorTrait>(line: Line<V>, r: Field) -> Option<Line<V>> {
     //construct the d cube planes, normals facing in
     let planes_iter = (0..V::DIM).flat_map(move |i| {
@@ -367,15 +409,159 @@ fn test_tube_clipping() {
     println!("{}", clipped_line.unwrap())
 }
 
+#[test]
+fn test_split_line() {
+    use crate::graphics::colors::WHITE;
+    use crate::vector::Vec2;
+    // lines are hidden where they are inside the slab -1 < x < 1
+    let boundaries = vec![
+        Plane {
+            normal: Vec2::new(1.0, 0.0),
+            threshold: 1.0,
+        },
+        Plane {
+            normal: Vec2::new(-1.0, 0.0),
+            threshold: 1.0,
+        },
+    ];
+    let line = Line(Vec2::new(-2.0, 0.0), Vec2::new(2.0, 0.0));
+    let (visible, hidden) = split_line(line, &boundaries);
+    assert!(matches!(visible, ReturnLines::TwoLines(..)));
+    assert!(hidden
+        .unwrap()
+        .is_close(&Line(Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.0))));
+    let line = Line(Vec2::new(-0.5, 0.0), Vec2::new(0.5, 1.0));
+    let (visible, hidden) = split_line(line.clone(), &boundaries);
+    assert!(matches!(visible, ReturnLines::NoLines));
+    assert!(hidden.unwrap().is_close(&line));
+
+    let ghost = GhostConfig::default();
+    let ghost_line = ghost_line(
+        DrawLine {
+            line,
+            color: WHITE,
+            end_color: None,
+            style: crate::draw::LineStyle::Solid,
+        },
+        &ghost,
+    );
+    assert_eq!(ghost_line.style, ghost.style);
+    assert_eq!(ghost_line.color.get_arr()[3], ghost.dim);
+}
+
+#[test]
+fn test_clip_line_planes_corner() {
+    use crate::vector::Vec2;
+    // the diamond |x| + |y| < 1
+    let planes: Vec<Plane<Vec2>> = [(1.0, 1.0), (1.0, -1.0), (-1.0, 1.0), (-1.0, -1.0)]
+        .into_iter()
+        .map(|(x, y)| Plane {
+            normal: Vec2::new(x, y).normalize(),
+            threshold: (0.5 as Field).sqrt(),
+        })
+        .collect();
+    // passes beside the corner at (1, 0), crossing the extended edges outside the diamond
+    let line = Line(Vec2::new(1.2, -0.5), Vec2::new(1.2, 0.5));
+    assert!(clip_line_planes(line, &planes).is_none());
+    let line = Line(Vec2::new(0.4, -0.5), Vec2::new(0.4, 0.5));
+    assert!(clip_line_planes(line, &planes)
+        .unwrap()
+        .is_close(&Line(Vec2::new(0.4, -0.5), Vec2::new(0.4, 0.5))));
+    let line = Line(Vec2::new(0.8, -0.5), Vec2::new(0.8, 0.5));
+    assert!(clip_line_planes(line, &planes)
+        .unwrap()
+        .is_close(&Line(Vec2::new(0.8, -0.2), Vec2::new(0.8, 0.2))));
+}
+
+#[test]
+fn test_ghost_lines_past_corner() {
+    use crate::graphics::colors::WHITE;
+    use crate::vector::Vec2;
+    // a shape whose boundaries make the diamond |x| + |y| < 1
+    let clip_state = ShapeClipState {
+        boundaries: [(1.0, 1.0), (1.0, -1.0), (-1.0, 1.0), (-1.0, -1.0)]
+            .into_iter()
+            .map(|(x, y)| Plane {
+                normal: Vec2::new(x, y).normalize(),
+                threshold: (0.5 as Field).sqrt(),
+            })
+            .collect(),
+        ..Default::default()
+    };
+    let draw_line = |line| DrawLine {
+        line,
+        color: WHITE,
+        end_color: None,
+        style: crate::draw::LineStyle::Solid,
+    };
+    let ghost = GhostConfig::default();
+    // a line passing beside a corner is drawn whole, with no ghost
+    let line = Line(Vec2::new(1.2, -0.5), Vec2::new(1.2, 0.5));
+    let lines = clip_draw_lines(
+        vec![draw_line(line.clone())],
+        std::iter::once(&clip_state),
+        Some(&ghost),
+    );
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].line.is_close(&line));
+    assert_eq!(lines[0].style, crate::draw::LineStyle::Solid);
+    // a line crossing the corner is split, and ghosted where it is hidden
+    let line = Line(Vec2::new(0.8, -0.5), Vec2::new(0.8, 0.5));
+    let lines = clip_draw_lines(
+        vec![draw_line(line)],
+        std::iter::once(&clip_state),
+        Some(&ghost),
+    );
+    assert_eq!(lines.len(), 3);
+    assert!(lines[2]
+        .line
+        .is_close(&Line(Vec2::new(0.8, -0.2), Vec2::new(0.8, 0.2))));
+    assert_eq!(lines[2].style, ghost.style);
+}
+
+#[test]
+fn test_draw_line_piece() {
+    use crate::graphics::colors::{BLACK, WHITE};
+    use crate::vector::Vec2;
+    let draw_line = DrawLine {
+        line: Line(Vec2::new(-2.0, 0.0), Vec2::new(2.0, 0.0)),
+        color: BLACK,
+        end_color: Some(WHITE),
+        style: crate::draw::LineStyle::Solid,
+    };
+    // the far half of the line fades from gray to white
+    let piece = draw_line_piece(&draw_line, Line(Vec2::new(0.0, 0.0), Vec2::new(2.0, 0.0)));
+    assert_eq!(piece.color.0, blend(BLACK, WHITE, 0.5).0);
+    assert_eq!(piece.end_color.map(|c| c.0), Some(WHITE.0));
+}
+
+#[test]
+fn test_clip_line_superellipsoid() {
+    use crate::vector::Vec3;
+    // with an exponent of 2, the superellipsoid is a sphere
+    let line = Line(Vec3::new(-2.0, 0.3, 0.1), Vec3::new(1.5, -0.2, 0.4));
+    let clipped = clip_line_superellipsoid(line.clone(), Vec3::ones(), 2.0).unwrap();
+    assert!(clipped.is_close(&clip_line_sphere(line, 1.0).unwrap()));
+    // a line passing outside the corner of the unit sphere is inside a squarer superellipsoid
+    let corner_line = Line(Vec3::new(0.8, 0.8, -2.0), Vec3::new(0.8, 0.8, 2.0));
+    assert!(clip_line_superellipsoid(corner_line.clone(), Vec3::ones(), 2.0).is_none());
+    let clipped = clip_line_superellipsoid(corner_line, Vec3::ones(), 8.0).unwrap();
+    assert!(clipped.0[2] > -1.0 && clipped.1[2] < 1.0);
+}
+
 pub enum ReturnLines<V> {
     TwoLines(Line<V>, Line<V>),
     OneLine(Line<V>),
     NoLines,
 }
-pub fn clip_line<V: VectorTrait>(line: Line<V>, boundaries: &Vec<Plane<V>>) -> ReturnLines<V> {
+// splits the line into the parts outside the boundaries, and the part between them
+pub fn split_line<V: VectorTrait>(
+    line: Line<V>,
+    boundaries: &Vec<Plane<V>>,
+) -> (ReturnLines<V>, Option<Line<V>>) {
     //if no boundaries, return original line
     if boundaries.is_empty() {
-        return ReturnLines::OneLine(line);
+        return (ReturnLines::OneLine(line), None);
     }
     let Line(p0, p1) = line;
 
@@ -408,40 +594,153 @@ pub fn clip_line<V: VectorTrait>(line: Line<V>, boundaries: &Vec<Plane<V>>) -> R
         p0_all_safe = p0_all_safe || p0_safe;
         p1_all_safe = p1_all_safe || p1_safe;
     }
+    let hidden = |a, b| Some(Line(V::linterp(p0, p1, a), V::linterp(p0, p1, b)));
     //both endpoints visible
     if p0_all_safe && p1_all_safe {
-        //return two lines if we've intersected the shape
-        if a > 0.0 && b < 1.0 {
-            return ReturnLines::TwoLines(
-                Line(p0, V::linterp(p0, p1, a)),
-                Line(V::linterp(p0, p1, b), p1),
+        //return two lines if we've intersected the shape. a line passing outside a corner
+        //crosses the planes there with a > b, and misses the shape
+        if a > 0.0 && b < 1.0 && a < b {
+            return (
+                ReturnLines::TwoLines(
+                    Line(p0, V::linterp(p0, p1, a)),
+                    Line(V::linterp(p0, p1, b), p1),
+                ),
+                hidden(a, b),
             );
         } else {
             //return entire line if we haven't intersected the shape
-            return ReturnLines::OneLine(line);
+            return (ReturnLines::OneLine(line), None);
         }
     }
     if p0_all_safe && !p1_all_safe {
-        return ReturnLines::OneLine(Line(p0, V::linterp(p0, p1, a)));
+        return (
+            ReturnLines::OneLine(Line(p0, V::linterp(p0, p1, a))),
+            hidden(a, 1.0),
+        );
     }
     if !p0_all_safe && p1_all_safe {
-        return ReturnLines::OneLine(Line(V::linterp(p0, p1, b), p1));
+        return (
+            ReturnLines::OneLine(Line(V::linterp(p0, p1, b), p1)),
+            hidden(0.0, b),
+        );
     }
     //if neither point is visible, don't draw the line
-    ReturnLines::NoLines
+    (ReturnLines::NoLines, Some(line))
+}
+
+// the part of the line inside all of the planes, whose normals face out
+pub fn clip_line_planes<V: VectorTrait>(line: Line<V>, planes: &Vec<Plane<V>>) -> Option<Line<V>> {
+    match planes.is_empty() {
+        true => Some(line),
+        false => split_line(line, planes).1,
+    }
+}
+
+// the part of the line inside the superellipsoid sum |x_i / r_i|^exponent <= 1, for exponents of
+// at least 1. the superellipsoid is convex, so its level function is convex along the line, and
+// the line's ends are found by bisection on either side of its minimum
+pub fn clip_line_superellipsoid<V: VectorTrait>(
+    line: Line<V>,
+    radii: V,
+    exponent: Field,
+) -> Option<Line<V>> {
+    const ITERATIONS: usize = 32;
+    let exponent = exponent.max(1.0);
+    let level = |t: Field| {
+        let p = line.linterp(t);
+        (0..V::DIM)
+            .map(|i| (p[i] / radii[i]).abs().powf(exponent))
+            .sum::<Field>()
+            - 1.0
+    };
+    // ternary search for the minimum
+    let (mut lo, mut hi) = (0.0 as Field, 1.0 as Field);
+    for _ in 0..ITERATIONS {
+        let (t0, t1) = (lo + (hi - lo) / 3.0, hi - (hi - lo) / 3.0);
+        if level(t0) < level(t1) {
+            hi = t1;
+        } else {
+            lo = t0;
+        }
+    }
+    let t_min = (lo + hi) / 2.0;
+    if level(t_min) > 0.0 {
+        return None;
+    }
+    // bisects between an inside and an outside point
+    let boundary = |mut inside: Field, mut outside: Field| {
+        if level(outside) <= 0.0 {
+            return outside;
+        }
+        for _ in 0..ITERATIONS {
+            let mid = (inside + outside) / 2.0;
+            if level(mid) <= 0.0 {
+                inside = mid;
+            } else {
+                outside = mid;
+            }
+        }
+        inside
+    };
+    Some(Line(
+        line.linterp(boundary(t_min, 0.0)),
+        line.linterp(boundary(t_min, 1.0)),
+    ))
+}
+
+// dims a hidden line and restyles it, for ghost mode
+fn ghost_line<V>(draw_line: DrawLine<V>, ghost: &GhostConfig) -> DrawLine<V> {
+    let dim = |color: Color| color.set_alpha(color.get_arr()[3] * ghost.dim);
+    DrawLine {
+        color: dim(draw_line.color),
+        end_color: draw_line.end_color.map(dim),
+        style: ghost.style,
+        ..draw_line
+    }
+}
+
+// the piece of a draw line along part of it. a line blending to end_color is blended
+// at the ends of the piece, so each piece shows only its part of the gradient
+fn draw_line_piece<V: VectorTrait>(draw_line: &DrawLine<V>, line: Line<V>) -> DrawLine<V> {
+    let Some(end_color) = draw_line.end_color else {
+        return DrawLine {
+            line,
+            ..draw_line.clone()
+        };
+    };
+    let Line(a, b) = draw_line.line;
+    let length2 = (b - a).dot(b - a);
+    let color_at = |p: V| match length2 > 0.0 {
+        true => blend(
+            draw_line.color,
+            end_color,
+            ((p - a).dot(b - a) / length2).clamp(0.0, 1.0),
+        ),
+        false => draw_line.color,
+    };
+    DrawLine {
+        color: color_at(line.0),
+        end_color: Some(color_at(line.1)),
+        line,
+        style: draw_line.style,
+    }
 }
 
 //consider using parallel joins here
 // TODO: reduce vec pushing + allocation (~10% of runtime)
+// with ghost mode, the hidden parts of lines are kept, dimmed and restyled.
+// ghost lines are not clipped by the shapes after the one that hides them
 pub fn clip_draw_lines<'a, V: VectorTrait + 'a, I>(
     lines: Vec<DrawLine<V>>,
     clip_states_in_front: I,
+    ghost: Option<&GhostConfig>,
 ) -> Vec<DrawLine<V>>
 //where for<'a> &'a I : std::iter::Iterator<Item=&'a ShapeClipState<V>>
 where
     I: std::iter::Iterator<Item = &'a ShapeClipState<V>>,
 {
     let mut clipped_lines = lines;
+    let mut ghost_lines: Vec<DrawLine<V>> = Vec::new();
 
     // let clipping_shapes : Vec<&Shape<V>> = match shape_in_front {
     //     Some(in_fronts) => shapes.join().zip(in_fronts)
@@ -456,7 +755,7 @@ where
         //     None => false
         // };
         //let same_shape = clip_shape_index == shape_index;
-        if !clipping_shape.transparent {
+        if !clipping_shape.transparent && !clipping_shape.culled {
             //let mut additional_lines : Vec<Option<Line<V>>> = Vec::new();
             let mut new_lines: Vec<DrawLine<V>> = Vec::new();
             //would like to map in place here, with side effects
@@ -466,14 +765,20 @@ where
             //right now i just push on to a new Vec every time
             for opt_draw_line in clipped_lines.into_iter() {
                 {
-                    let color = opt_draw_line.color;
-                    match clip_line(opt_draw_line.line, &clipping_shape.boundaries) {
+                    let (visible_lines, hidden_line) =
+                        split_line(opt_draw_line.line.clone(), &clipping_shape.boundaries);
+                    if let (Some(ghost), Some(line)) = (ghost, hidden_line) {
+                        ghost_lines.push(ghost_line(draw_line_piece(&opt_draw_line, line), ghost));
+                    }
+                    match visible_lines {
                         ReturnLines::TwoLines(line0, line1) => {
                             //additional_lines.push(Some(line1)); //push extra lines on to other vector
-                            new_lines.push(DrawLine { line: line0, color });
-                            new_lines.push(DrawLine { line: line1, color });
+                            new_lines.push(draw_line_piece(&opt_draw_line, line0));
+                            new_lines.push(draw_line_piece(&opt_draw_line, line1));
+                        }
+                        ReturnLines::OneLine(line) => {
+                            new_lines.push(draw_line_piece(&opt_draw_line, line))
                         }
-                        ReturnLines::OneLine(line) => new_lines.push(DrawLine { line, color }),
                         ReturnLines::NoLines => (),
                     }
                 }
@@ -482,6 +787,7 @@ where
             //clipped_lines.append(&mut additional_lines);
         }
     }
+    clipped_lines.append(&mut ghost_lines);
     clipped_lines
 }
 
@@ -1,5 +1,6 @@
 use crate::vector::{Field, VectorTrait};
 
+#[derive(Clone, Copy)]
 pub struct BBall<V> {
     pub pos: V,
     pub radius: Field,
@@ -9,9 +10,39 @@ impl<V: VectorTrait> BBall<V> {
     pub fn new(verts: &[V], pos: V) -> Self {
         let radius = verts
             .iter()
-            .map(|v| v.norm_sq())
+            .map(|v| (*v - pos).norm_sq())
             .fold(Field::NAN, Field::max)
             .sqrt();
         Self { pos, radius }
     }
+    /// smallest ball containing both balls
+    pub fn merged_with(&self, other: &Self) -> Self {
+        let disp = other.pos - self.pos;
+        let d = disp.norm();
+        if d + other.radius <= self.radius {
+            return *self;
+        }
+        if d + self.radius <= other.radius {
+            return *other;
+        }
+        let radius = (d + self.radius + other.radius) / 2.0;
+        Self {
+            pos: self.pos + disp * ((radius - self.radius) / d),
+            radius,
+        }
+    }
+}
+
+#[test]
+fn test_bball_radius() {
+    use crate::geometry::shape::buildshapes::ShapeBuilder;
+    use crate::geometry::transform::Transformable;
+    use crate::vector::{is_close, Vec3};
+
+    let pos = Vec3::new(10.0, 0.0, -5.0);
+    let shape = ShapeBuilder::<Vec3>::build_cube(2.0)
+        .with_translation(pos)
+        .build();
+    let bball = BBall::new(&shape.verts, pos);
+    assert!(is_close(bball.radius, Field::sqrt(3.0)));
 }
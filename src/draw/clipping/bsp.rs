@@ -0,0 +1,410 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::geometry::{Plane, Shape};
+use crate::vector::{Field, VectorTrait};
+
+use super::bball::BBall;
+use super::{dynamic_separate, Separation};
+
+// leaves holding more than this many items are split along one of their face planes
+const MAX_LEAF_ITEMS: usize = 2;
+// points within this distance of a splitting plane count as lying on it
+const PLANE_EPSILON: Field = 1e-4;
+
+type NodeIndex = usize;
+const ROOT: NodeIndex = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Front,
+    Back,
+    Spanning,
+}
+
+/// classify a set of points with respect to a plane.
+/// point sets lying entirely within the plane are considered spanning.
+pub fn classify_points<V: VectorTrait>(plane: &Plane<V>, points: &[V]) -> Side {
+    let (mut front, mut back) = (false, false);
+    for &p in points {
+        let d = plane.point_signed_distance(p);
+        front |= d > PLANE_EPSILON;
+        back |= d < -PLANE_EPSILON;
+    }
+    match (front, back) {
+        (true, false) => Side::Front,
+        (false, true) => Side::Back,
+        _ => Side::Spanning,
+    }
+}
+
+struct BspItem<V> {
+    verts: Vec<V>,
+    planes: Vec<Plane<V>>,
+    bball: BBall<V>,
+    node: NodeIndex,
+}
+
+struct BspNode<V, T> {
+    plane: Option<Plane<V>>,
+    parent: Option<NodeIndex>,
+    front: Option<NodeIndex>,
+    back: Option<NodeIndex>,
+    // items that span (or lie within) this node's plane, or all items if this is a leaf
+    items: Vec<T>,
+    // bounds everything in this subtree. None if the subtree is empty
+    bball: Option<BBall<V>>,
+}
+impl<V, T> BspNode<V, T> {
+    fn new(parent: Option<NodeIndex>) -> Self {
+        Self {
+            plane: None,
+            parent,
+            front: None,
+            back: None,
+            items: Vec::new(),
+            bball: None,
+        }
+    }
+}
+
+/// BSP tree over (static) shapes, split along their face hyperplanes.
+/// Items can be inserted and removed incrementally; splitting planes are never moved,
+/// so a modified shape is simply removed and reinserted.
+/// Like SpatialHashSet, T is the item id (Entity in the engine)
+pub struct BspTree<V, T> {
+    nodes: Vec<BspNode<V, T>>,
+    items: HashMap<T, BspItem<V>>,
+}
+
+impl<V: VectorTrait, T: Copy + Eq + Hash> Default for BspTree<V, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: VectorTrait, T: Copy + Eq + Hash> BspTree<V, T> {
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![BspNode::new(None)],
+            items: HashMap::new(),
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+    pub fn contains(&self, id: &T) -> bool {
+        self.items.contains_key(id)
+    }
+
+    pub fn insert(&mut self, id: T, shape: &Shape<V>, bball: &BBall<V>) {
+        self.remove(&id);
+        let item = BspItem {
+            verts: shape.verts.clone(),
+            planes: shape
+                .faces
+                .iter()
+                .map(|face| face.plane().clone())
+                .collect(),
+            bball: *bball,
+            node: ROOT,
+        };
+        let mut node_i = ROOT;
+        while let Some(plane) = &self.nodes[node_i].plane {
+            node_i = match classify_points(plane, &item.verts) {
+                Side::Front => self.child_or_new(node_i, Side::Front),
+                Side::Back => self.child_or_new(node_i, Side::Back),
+                Side::Spanning => break,
+            };
+        }
+        self.items.insert(
+            id,
+            BspItem {
+                node: node_i,
+                ..item
+            },
+        );
+        self.nodes[node_i].items.push(id);
+        self.update_bounds(node_i);
+        self.split(node_i);
+    }
+
+    pub fn remove(&mut self, id: &T) {
+        if let Some(item) = self.items.remove(id) {
+            self.nodes[item.node].items.retain(|i| i != id);
+            self.update_bounds(item.node);
+        }
+    }
+
+    fn child_or_new(&mut self, node_i: NodeIndex, side: Side) -> NodeIndex {
+        let existing = match side {
+            Side::Front => self.nodes[node_i].front,
+            Side::Back => self.nodes[node_i].back,
+            Side::Spanning => panic!("No child on spanning side"),
+        };
+        existing.unwrap_or_else(|| {
+            let child_i = self.nodes.len();
+            self.nodes.push(BspNode::new(Some(node_i)));
+            match side {
+                Side::Front => self.nodes[node_i].front = Some(child_i),
+                _ => self.nodes[node_i].back = Some(child_i),
+            };
+            child_i
+        })
+    }
+
+    // choose the face plane that best balances the node's items while cutting as few as possible
+    fn choose_plane(&self, node_i: NodeIndex) -> Option<Plane<V>> {
+        let ids = &self.nodes[node_i].items;
+        ids.iter()
+            .flat_map(|id| self.items[id].planes.iter())
+            .filter_map(|plane| {
+                let (mut front, mut back, mut spanning) = (0, 0, 0);
+                for id in ids {
+                    match classify_points(plane, &self.items[id].verts) {
+                        Side::Front => front += 1,
+                        Side::Back => back += 1,
+                        Side::Spanning => spanning += 1,
+                    }
+                }
+                (front > 0 && back > 0).then(|| {
+                    let score = front.max(back) - front.min(back) + 2 * spanning;
+                    (score, plane)
+                })
+            })
+            .min_by_key(|(score, _)| *score)
+            .map(|(_, plane)| plane.clone())
+    }
+
+    fn split(&mut self, node_i: NodeIndex) {
+        if self.nodes[node_i].plane.is_some() || self.nodes[node_i].items.len() <= MAX_LEAF_ITEMS {
+            return;
+        }
+        let plane = match self.choose_plane(node_i) {
+            Some(plane) => plane,
+            None => return,
+        };
+        let ids = std::mem::take(&mut self.nodes[node_i].items);
+        let mut children = Vec::new();
+        for id in ids {
+            let side = classify_points(&plane, &self.items[&id].verts);
+            let dest = match side {
+                Side::Spanning => node_i,
+                _ => self.child_or_new(node_i, side),
+            };
+            self.nodes[dest].items.push(id);
+            self.items.get_mut(&id).unwrap().node = dest;
+            if dest != node_i && !children.contains(&dest) {
+                children.push(dest)
+            }
+        }
+        self.nodes[node_i].plane = Some(plane);
+        for child_i in children {
+            self.update_bounds(child_i);
+            self.split(child_i);
+        }
+    }
+
+    // recompute bounding balls from node_i up to the root
+    fn update_bounds(&mut self, node_i: NodeIndex) {
+        let mut maybe_node_i = Some(node_i);
+        while let Some(node_i) = maybe_node_i {
+            let node = &self.nodes[node_i];
+            let bball = node
+                .items
+                .iter()
+                .map(|id| self.items[id].bball)
+                .chain(
+                    [node.front, node.back]
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|child_i| self.nodes[child_i].bball),
+                )
+                .reduce(|a, b| a.merged_with(&b));
+            self.nodes[node_i].bball = bball;
+            maybe_node_i = self.nodes[node_i].parent;
+        }
+    }
+
+    /// all items in front-to-back order as seen from origin.
+    /// items sharing a node are ordered by the distance to their centers
+    pub fn front_to_back(&self, origin: &V) -> Vec<T> {
+        let mut out = Vec::with_capacity(self.items.len());
+        self.collect_front_to_back(ROOT, origin, &mut out);
+        out
+    }
+    fn collect_front_to_back(&self, node_i: NodeIndex, origin: &V, out: &mut Vec<T>) {
+        let node = &self.nodes[node_i];
+        let (near, far) = self.near_far(node, origin);
+        if let Some(near) = near {
+            self.collect_front_to_back(near, origin, out)
+        }
+        out.extend(self.sorted_items(node, origin));
+        if let Some(far) = far {
+            self.collect_front_to_back(far, origin, out)
+        }
+    }
+    fn sorted_items(&self, node: &BspNode<V, T>, origin: &V) -> Vec<T> {
+        let mut items = node.items.clone();
+        items.sort_by(|a, b| {
+            let da = (self.items[a].bball.pos - *origin).norm_sq();
+            let db = (self.items[b].bball.pos - *origin).norm_sq();
+            da.total_cmp(&db)
+        });
+        items
+    }
+    fn near_far(&self, node: &BspNode<V, T>, origin: &V) -> (Option<NodeIndex>, Option<NodeIndex>) {
+        match &node.plane {
+            Some(plane) if plane.point_signed_distance(*origin) < 0.0 => (node.back, node.front),
+            _ => (node.front, node.back),
+        }
+    }
+
+    /// items in the tree that may be in front of the item id, in front-to-back order.
+    /// whole subtrees are skipped when they are behind the item (as seen from origin)
+    /// or out of its line of sight. may_occlude performs the final pairwise test.
+    pub fn occluders_of_item<F>(&self, id: &T, origin: &V, may_occlude: F) -> Vec<T>
+    where
+        F: FnMut(T) -> bool,
+    {
+        match self.items.get(id) {
+            Some(item) => self.occluders(&item.verts, &item.bball, Some(id), origin, may_occlude),
+            None => Vec::new(),
+        }
+    }
+
+    /// as occluders_of_item, for a shape that may or may not be in the tree
+    pub fn occluders<F>(
+        &self,
+        verts: &[V],
+        bball: &BBall<V>,
+        exclude: Option<&T>,
+        origin: &V,
+        mut may_occlude: F,
+    ) -> Vec<T>
+    where
+        F: FnMut(T) -> bool,
+    {
+        let mut out = Vec::new();
+        let probe = Probe {
+            verts,
+            bball,
+            exclude,
+        };
+        self.collect_occluders(ROOT, &probe, origin, &mut may_occlude, &mut out);
+        out
+    }
+
+    fn collect_occluders<F>(
+        &self,
+        node_i: NodeIndex,
+        probe: &Probe<V, T>,
+        origin: &V,
+        may_occlude: &mut F,
+        out: &mut Vec<T>,
+    ) where
+        F: FnMut(T) -> bool,
+    {
+        let node = &self.nodes[node_i];
+        // nothing in this subtree can occlude the probe if the subtree's ball is
+        // behind the probe or does not overlap it on screen
+        match &node.bball {
+            None => return,
+            Some(node_bball) => {
+                if matches!(
+                    dynamic_separate(node_bball, probe.bball, origin),
+                    Separation::NoFront | Separation::S1Front
+                ) {
+                    return;
+                }
+            }
+        }
+        let (near, far) = self.near_far(node, origin);
+        if let Some(near) = near {
+            self.collect_occluders(near, probe, origin, may_occlude, out);
+        }
+        for id in self.sorted_items(node, origin) {
+            if Some(&id) != probe.exclude && may_occlude(id) {
+                out.push(id)
+            }
+        }
+        // shapes entirely on the far side of the plane cannot occlude a probe on the near side
+        let probe_on_near_side = match &node.plane {
+            Some(plane) => {
+                let camera_side = match plane.point_signed_distance(*origin) < 0.0 {
+                    true => Side::Back,
+                    false => Side::Front,
+                };
+                classify_points(plane, probe.verts) == camera_side
+            }
+            None => false,
+        };
+        if let (Some(far), false) = (far, probe_on_near_side) {
+            self.collect_occluders(far, probe, origin, may_occlude, out);
+        }
+    }
+}
+
+struct Probe<'a, V, T> {
+    verts: &'a [V],
+    bball: &'a BBall<V>,
+    exclude: Option<&'a T>,
+}
+
+#[cfg(test)]
+fn row_of_cubes(n: usize) -> BspTree<crate::vector::Vec3, usize> {
+    use crate::geometry::shape::buildshapes::ShapeBuilder;
+    use crate::geometry::transform::Transformable;
+    use crate::vector::Vec3;
+
+    let mut tree = BspTree::new();
+    for i in 0..n {
+        let pos = Vec3::new(0.0, 0.0, 2.0 * i as Field);
+        let shape: Shape<Vec3> = ShapeBuilder::build_cube(1.0).with_translation(pos).build();
+        tree.insert(i, &shape, &BBall::new(&shape.verts, pos));
+    }
+    tree
+}
+
+#[test]
+fn test_bsp_front_to_back() {
+    use crate::vector::Vec3;
+    let mut tree = row_of_cubes(8);
+    assert_eq!(tree.len(), 8);
+    assert_eq!(
+        tree.front_to_back(&Vec3::new(0.0, 0.0, -10.0)),
+        (0..8).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        tree.front_to_back(&Vec3::new(0.0, 0.0, 30.0)),
+        (0..8).rev().collect::<Vec<_>>()
+    );
+    tree.remove(&3);
+    assert!(!tree.contains(&3));
+    assert_eq!(
+        tree.front_to_back(&Vec3::new(0.0, 0.0, -10.0)),
+        vec![0, 1, 2, 4, 5, 6, 7]
+    );
+}
+
+#[test]
+fn test_bsp_occluders() {
+    use crate::vector::Vec3;
+    let tree = row_of_cubes(8);
+    let origin = Vec3::new(0.0, 0.0, -10.0);
+    // nearest cube can't be occluded
+    assert!(tree.occluders_of_item(&0, &origin, |_| true).is_empty());
+    // everything nearer may occlude the farthest cube
+    assert_eq!(
+        tree.occluders_of_item(&7, &origin, |_| true),
+        (0..7).collect::<Vec<_>>()
+    );
+    // the pairwise test has the final say
+    assert_eq!(
+        tree.occluders_of_item(&7, &origin, |id| id % 2 == 0),
+        vec![0, 2, 4, 6]
+    );
+}
@@ -5,13 +5,14 @@ use specs::{Join, ReadExpect, ReadStorage, System, WriteExpect};
 
 use crate::{
     components::{ClipState, ShapeClipState},
+    config::GhostConfig,
     ecs_utils::Componentable,
     geometry::Line,
     graphics::colors::Color,
     vector::VectorTrait,
 };
 
-use super::{clipping::clip_draw_lines, DrawLine, DrawLineList};
+use super::{clipping::clip_draw_lines, DrawLine, DrawLineList, LineStyle};
 
 pub struct DrawLineCollection<V>(pub Vec<DrawLine<V>>);
 impl<V> DrawLineCollection<V> {
@@ -19,11 +20,23 @@ impl<V> DrawLineCollection<V> {
         Self(
             lines
                 .into_iter()
-                .map(|line| DrawLine { line, color })
+                .map(|line| DrawLine {
+                    line,
+                    color,
+                    end_color: None,
+                    style: LineStyle::Solid,
+                })
                 .collect(),
         )
     }
 
+    pub fn with_style(mut self, style: LineStyle) -> Self {
+        for draw_line in self.0.iter_mut() {
+            draw_line.style = style;
+        }
+        self
+    }
+
     pub(crate) fn extend<I>(mut self, iter: I) -> Self
     where
         I: Iterator<Item = DrawLine<V>>,
@@ -36,6 +49,7 @@ impl<V> DrawLineCollection<V> {
 pub fn draw_collection<'a, V: VectorTrait + 'a, I>(
     lines_collection: &DrawLineCollection<V>,
     shape_clip_state_iter: Option<I>,
+    ghost: Option<&GhostConfig>,
 ) -> Vec<DrawLine<V>>
 where
     I: std::iter::Iterator<Item = &'a ShapeClipState<V>>,
@@ -44,7 +58,7 @@ where
     // TODO: eliminate cloning here?
     let lines = lines_collection.0.clone();
     match shape_clip_state_iter {
-        Some(iter) => clip_draw_lines(lines, iter),
+        Some(iter) => clip_draw_lines(lines, iter, ghost),
         None => lines,
     }
 }
@@ -0,0 +1,199 @@
+use crate::components::{Camera, Shape, ShapeClipState, ShapeTexture, Transform};
+use crate::config::ViewConfig;
+use crate::constants::Z_NEAR;
+use crate::geometry::{Face, Plane};
+use crate::graphics::colors::Color;
+use crate::vector::{barycenter, VectorTrait};
+
+use super::clipping::clip_polygon_plane;
+use super::viewport::viewport_planes;
+use super::{project, view_transform, ViewportShape};
+
+#[derive(Clone)]
+pub struct DrawTriangle<V> {
+    pub verts: [V; 3],
+    pub color: Color,
+}
+
+// filled faces are blended additively, so they can be drawn in any order
+pub struct DrawTriangleList<V>(pub Vec<DrawTriangle<V>>);
+impl<V> Default for DrawTriangleList<V> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+// triangles fanning out from the face center to each edge.
+// these cover the polygon faces of 3d shapes exactly. the polyhedral faces of 4d shapes
+// are filled through their interior, with one triangle per edge
+fn face_triangles<'a, V: VectorTrait>(
+    shape: &'a Shape<V>,
+    face: &'a Face<V>,
+) -> impl Iterator<Item = [V; 3]> + 'a {
+    face.edgeis.iter().map(move |&edgei| {
+        let edge = &shape.edges[edgei];
+        [face.center(), shape.verts[edge.0], shape.verts[edge.1]]
+    })
+}
+
+// planes of the viewport, with normals facing in. None for curved viewports, which faces can't
+// be clipped to exactly, so they aren't filled
+pub fn viewport_fill_planes<U: VectorTrait>(view_config: &ViewConfig) -> Option<Vec<Plane<U>>> {
+    let r = view_config.radius;
+    match &view_config.viewport_shape {
+        ViewportShape::Cube => Some(
+            (0..U::DIM)
+                .flat_map(|i| {
+                    [-1.0, 1.0].map(|sign| Plane {
+                        normal: U::one_hot(i) * sign,
+                        threshold: -r,
+                    })
+                })
+                .collect(),
+        ),
+        ViewportShape::Planes(planes) => Some(
+            viewport_planes::<U>(planes)
+                .into_iter()
+                .map(|plane| Plane {
+                    normal: -plane.normal,
+                    threshold: -plane.threshold,
+                })
+                .collect(),
+        ),
+        ViewportShape::None => Some(Vec::new()),
+        ViewportShape::Sphere
+        | ViewportShape::Cylinder
+        | ViewportShape::Tube
+        | ViewportShape::Superellipsoid { .. } => None,
+    }
+}
+
+// clips the triangle to the camera half-space and the viewport planes, then projects it.
+// the clipped polygon is split back into triangles
+fn transform_triangle<V: VectorTrait>(
+    verts: [V; 3],
+    color: Color,
+    transform: &Transform<V, V::M>,
+    camera: &Camera<V>,
+    view_config: &ViewConfig,
+    viewport_planes: &[Plane<V::SubV>],
+) -> Vec<DrawTriangle<V::SubV>> {
+    let view_polygon: Vec<V> = clip_polygon_plane(verts.to_vec(), &camera.plane, Z_NEAR)
+        .into_iter()
+        .map(|v| view_transform(transform, v))
+        .collect();
+    if view_polygon.is_empty() {
+        return Vec::new();
+    }
+    let factor = view_config
+        .depth_cue
+        .factor(barycenter(&view_polygon).norm());
+    let color = color.set_alpha(color.get_arr()[3] * factor);
+    let projected_polygon = viewport_planes.iter().fold(
+        view_polygon
+            .into_iter()
+            .map(|v| project(view_config.projection, view_config.focal, v))
+            .collect(),
+        |polygon, plane| clip_polygon_plane(polygon, plane, 0.0),
+    );
+    (1..projected_polygon.len().saturating_sub(1))
+        .map(|i| DrawTriangle {
+            verts: [
+                projected_polygon[0],
+                projected_polygon[i],
+                projected_polygon[i + 1],
+            ],
+            color,
+        })
+        .collect()
+}
+
+// fills the visible faces of a shape with their texture colors
+pub fn calc_shape_fills<V: VectorTrait>(
+    shape: &Shape<V>,
+    shape_texture: &ShapeTexture<V::SubV>,
+    shape_clip_state: &ShapeClipState<V>,
+    transform: &Transform<V, V::M>,
+    camera: &Camera<V>,
+    view_config: &ViewConfig,
+    viewport_planes: &[Plane<V::SubV>],
+) -> Vec<DrawTriangle<V::SubV>> {
+    shape
+        .faces
+        .iter()
+        .zip(shape_texture.face_textures.iter())
+        .zip(shape_clip_state.face_visibility.iter())
+        .enumerate()
+        .filter(|(_, (_, &visible))| visible)
+        .flat_map(|(face_i, ((face, face_texture), _))| {
+            let brightness = shape_clip_state
+                .face_brightness
+                .get(face_i)
+                .copied()
+                .unwrap_or(1.0);
+            let color = face_texture.texture.color().shade(brightness);
+            let color = color.set_alpha(color.get_arr()[3] * view_config.fill.alpha);
+            face_triangles(shape, face)
+                .flat_map(|verts| {
+                    transform_triangle(
+                        verts,
+                        color,
+                        transform,
+                        camera,
+                        view_config,
+                        viewport_planes,
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[test]
+fn test_clip_polygon_plane() {
+    use crate::vector::Vec2;
+    let square = vec![
+        Vec2::new(-1.0, -1.0),
+        Vec2::new(1.0, -1.0),
+        Vec2::new(1.0, 1.0),
+        Vec2::new(-1.0, 1.0),
+    ];
+    let plane = Plane {
+        normal: Vec2::new(1.0, 0.0),
+        threshold: 0.0,
+    };
+    let clipped = clip_polygon_plane(square, &plane, 0.0);
+    assert_eq!(clipped.len(), 4);
+    assert!(clipped.iter().all(|v| v[0] >= 0.0));
+    let area: f32 = clipped
+        .iter()
+        .zip(clipped.iter().cycle().skip(1))
+        .map(|(a, b)| a[0] * b[1] - a[1] * b[0])
+        .sum::<f32>()
+        / 2.0;
+    assert!((area - 2.0).abs() < 1e-5);
+}
+
+#[test]
+fn test_viewport_fill_planes() {
+    use crate::vector::Vec2;
+    let mut view_config = ViewConfig {
+        viewport_shape: ViewportShape::Cube,
+        ..Default::default()
+    };
+    let planes = viewport_fill_planes::<Vec2>(&view_config).unwrap();
+    assert_eq!(planes.len(), 4);
+    // a triangle poking out of the viewport is cut off at its edge
+    let triangle = vec![
+        Vec2::new(0.0, 0.0),
+        Vec2::new(2.0, 0.0),
+        Vec2::new(0.0, 0.1),
+    ];
+    let clipped = planes.iter().fold(triangle, |polygon, plane| {
+        clip_polygon_plane(polygon, plane, 0.0)
+    });
+    assert!(clipped.iter().all(|v| v[0] <= view_config.radius + 1e-5));
+    // curved viewports aren't filled
+    view_config.viewport_shape = ViewportShape::Sphere;
+    assert!(viewport_fill_planes::<Vec2>(&view_config).is_none());
+}
@@ -0,0 +1,62 @@
+use crate::config::LightConfig;
+use crate::vector::{Field, VectorTrait};
+
+// the light config of the current level. levels without their own light use the configured one
+pub struct LevelLight(pub LightConfig);
+
+// a directional light shining on every face of the scene
+pub struct Light<V> {
+    // unit vector pointing towards the light
+    pub direction: V,
+    // brightness of faces turned away from the light, between 0 and 1
+    pub ambient: Field,
+}
+// without a light, faces keep their texture colors
+impl<V: VectorTrait> Default for Light<V> {
+    fn default() -> Self {
+        Self {
+            direction: V::one_hot(1),
+            ambient: 1.0,
+        }
+    }
+}
+impl<V: VectorTrait> Light<V> {
+    // components of the configured direction beyond the dimension are dropped, and missing ones are zero
+    pub fn from_config(config: &LightConfig) -> Self {
+        if !config.enabled {
+            return Self::default();
+        }
+        let components: Vec<Field> = (0..V::DIM as usize)
+            .map(|i| config.direction.get(i).copied().unwrap_or(0.0))
+            .collect();
+        let direction = V::from_iter(components.iter());
+        let norm = direction.norm();
+        if norm == 0.0 {
+            return Self::default();
+        }
+        Self {
+            direction: direction / norm,
+            ambient: config.ambient.clamp(0.0, 1.0),
+        }
+    }
+    // lambertian brightness of a face with the given outward normal
+    pub fn brightness(&self, normal: V) -> Field {
+        self.ambient + (1.0 - self.ambient) * normal.dot(self.direction).max(0.0)
+    }
+}
+
+#[test]
+fn test_light_brightness() {
+    use crate::vector::Vec4;
+    let light = Light::<Vec4>::from_config(&LightConfig {
+        enabled: true,
+        direction: vec![0.0, 2.0],
+        ambient: 0.25,
+    });
+    assert!(Vec4::is_close(light.direction, Vec4::one_hot(1)));
+    assert_eq!(light.brightness(Vec4::one_hot(1)), 1.0);
+    assert_eq!(light.brightness(-Vec4::one_hot(1)), 0.25);
+    assert_eq!(light.brightness(Vec4::one_hot(3)), 0.25);
+    let tilted = (Vec4::one_hot(1) + Vec4::one_hot(3)) / (2.0 as Field).sqrt();
+    assert!((light.brightness(tilted) - (0.25 + 0.75 / (2.0 as Field).sqrt())).abs() < 1e-5);
+}
@@ -0,0 +1,34 @@
+use super::DrawLine;
+
+/// world-space texture lines for each face of a shape, kept between frames.
+/// cleared whenever the shape or its texture changes; faces that are not visible hold no lines.
+pub struct ShapeLinesCache<V> {
+    face_lines: Vec<Option<Vec<DrawLine<V>>>>,
+}
+impl<V> Default for ShapeLinesCache<V> {
+    fn default() -> Self {
+        Self {
+            face_lines: Vec::new(),
+        }
+    }
+}
+impl<V> ShapeLinesCache<V> {
+    pub fn clear(&mut self) {
+        self.face_lines.clear();
+    }
+    /// cached lines for face face_i, computed with f if there are none
+    pub fn get_or_insert_with<F>(&mut self, face_i: usize, f: F) -> &Vec<DrawLine<V>>
+    where
+        F: FnOnce() -> Vec<DrawLine<V>>,
+    {
+        if self.face_lines.len() <= face_i {
+            self.face_lines.resize_with(face_i + 1, || None);
+        }
+        self.face_lines[face_i].get_or_insert_with(f)
+    }
+    pub fn remove(&mut self, face_i: usize) {
+        if let Some(lines) = self.face_lines.get_mut(face_i) {
+            *lines = None;
+        }
+    }
+}
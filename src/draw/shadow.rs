@@ -0,0 +1,79 @@
+use crate::components::{BBox, Shape, ShapeType, ShapeTypeTrait};
+use crate::constants::{MAX_SHADOW_DIST, SHADOW_COLOR, SHADOW_LIFT};
+use crate::geometry::Line;
+use crate::vector::{Field, VectorTrait};
+
+use super::draw_line_collection::DrawLineCollection;
+use super::LineStyle;
+
+// shows the entity's position on the ground below it. the marker is updated each frame,
+// and kept apart from the entity's own DrawLineCollection (e.g. its selection box)
+pub struct DropShadow<V> {
+    pub marker: DrawLineCollection<V>,
+}
+impl<V> Default for DropShadow<V> {
+    fn default() -> Self {
+        Self {
+            marker: DrawLineCollection(Vec::new()),
+        }
+    }
+}
+
+// the highest point of the colliders directly below pos
+pub fn cast_shadow<'a, V: VectorTrait + 'a, I>(pos: V, colliders: I) -> Option<V>
+where
+    I: Iterator<Item = (&'a Shape<V>, &'a ShapeType<V>)>,
+{
+    let ray = Line(pos, pos - V::one_hot(1) * MAX_SHADOW_DIST);
+    colliders
+        .flat_map(|(shape, shape_type)| {
+            let all_faces = vec![true; shape.faces.len()];
+            shape_type.line_intersect(shape, &ray, false, &all_faces)
+        })
+        .fold(None, |highest: Option<V>, point| match highest {
+            Some(h) if h[1] >= point[1] => highest,
+            _ => Some(point),
+        })
+}
+
+// the column below pos that cast_shadow searches
+pub fn shadow_column<V: VectorTrait>(pos: V) -> BBox<V> {
+    BBox {
+        min: pos - V::one_hot(1) * MAX_SHADOW_DIST,
+        max: pos,
+    }
+}
+
+// a cross along each horizontal axis, lifted slightly so the ground doesn't clip it
+pub fn shadow_marker<V: VectorTrait>(point: V, size: Field) -> DrawLineCollection<V> {
+    let center = point + V::one_hot(1) * SHADOW_LIFT;
+    DrawLineCollection::from_lines(
+        (0..V::DIM)
+            .filter(|&i| i != 1)
+            .map(|i| Line(center - V::one_hot(i) * size, center + V::one_hot(i) * size))
+            .collect(),
+        SHADOW_COLOR,
+    )
+    .with_style(LineStyle::Dotted)
+}
+
+#[test]
+fn test_cast_shadow() {
+    use crate::geometry::shape::buildshapes::ShapeBuilder;
+    use crate::geometry::shape::Convex;
+    use crate::vector::Vec3;
+
+    let mut floor = ShapeBuilder::<Vec3>::build_cube(2.0).build();
+    floor.update_from_ref(
+        &floor.clone(),
+        &crate::components::Transform::pos(Vec3::new(0.0, -3.0, 0.0)),
+    );
+    let floor_type = ShapeType::Convex(Convex::new(&floor));
+    let colliders = || std::iter::once((&floor, &floor_type));
+    let hit = cast_shadow(Vec3::new(0.5, 1.0, 0.0), colliders()).unwrap();
+    assert!(Vec3::is_close(hit, Vec3::new(0.5, -2.0, 0.0)));
+    // nothing below
+    assert!(cast_shadow(Vec3::new(3.0, 1.0, 0.0), colliders()).is_none());
+    // the ray doesn't reach upwards
+    assert!(cast_shadow(Vec3::new(0.0, -5.0, 0.0), colliders()).is_none());
+}
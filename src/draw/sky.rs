@@ -0,0 +1,299 @@
+use itertools::Itertools;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{
+    draw_line_collection::DrawLineCollection, light::Light, visual_aids::draw_stars, DrawLine,
+    LineStyle,
+};
+use crate::config::{LightConfig, SceneConfig, SkyConfig};
+use crate::constants::{PI, SKY_DISTANCE, STAR_SIZE};
+use crate::geometry::Line;
+use crate::graphics::colors::{blend, Color};
+use crate::vector::{Field, VecIndex, VectorTrait};
+
+// segments in each circle drawn in the sky
+const SKY_SEGMENTS: usize = 48;
+// opacity of the lines joining the stars of a constellation, relative to the stars
+const CONSTELLATION_ALPHA: Field = 0.3;
+const UP_AXIS: VecIndex = 1;
+
+// lines of the sky relative to the player. they are drawn around the player each frame,
+// so the sky keeps its orientation in the world but is never any closer
+pub struct Sky<V> {
+    lines: Vec<DrawLine<V>>,
+    built_from: Option<SkyParams>,
+}
+impl<V> Default for Sky<V> {
+    fn default() -> Self {
+        Self {
+            lines: Vec::new(),
+            built_from: None,
+        }
+    }
+}
+
+// the parts of the scene config that the sky is built from
+#[derive(PartialEq)]
+struct SkyParams {
+    sky: bool,
+    horizon: bool,
+    stars: bool,
+    config: SkyConfig,
+    light: LightConfig,
+}
+impl SkyParams {
+    fn new(scene: &SceneConfig, light: &LightConfig) -> Self {
+        Self {
+            sky: scene.sky,
+            horizon: scene.horizon,
+            stars: scene.stars,
+            config: scene.sky_dome.clone(),
+            light: light.clone(),
+        }
+    }
+}
+
+impl<V: VectorTrait> Sky<V> {
+    // rebuild the sky if the scene config or the level's light has changed since it was built.
+    // returns whether it was rebuilt
+    pub fn update(&mut self, scene: &SceneConfig, light: &LightConfig) -> bool {
+        let params = SkyParams::new(scene, light);
+        if self.built_from.as_ref() == Some(&params) {
+            return false;
+        }
+        self.lines = build_sky(&params);
+        self.built_from = Some(params);
+        true
+    }
+
+    pub fn lines_around(&self, pos: V) -> DrawLineCollection<V> {
+        DrawLineCollection(
+            self.lines
+                .iter()
+                .map(|draw_line| DrawLine {
+                    line: draw_line.line.map(|p| p + pos),
+                    ..draw_line.clone()
+                })
+                .collect(),
+        )
+    }
+}
+
+fn horizontal_axes<V: VectorTrait>() -> Vec<VecIndex> {
+    (0..V::DIM).filter(|&i| i != UP_AXIS).collect()
+}
+
+fn circle<V: VectorTrait>(center: V, u: V, v: V, radius: Field) -> Vec<Line<V>> {
+    let point = |s: usize| {
+        let angle = 2.0 * PI * (s as Field) / (SKY_SEGMENTS as Field);
+        center + (u * angle.cos() + v * angle.sin()) * radius
+    };
+    (0..SKY_SEGMENTS)
+        .map(|s| Line(point(s), point(s + 1)))
+        .collect()
+}
+
+// circles at constant elevation, one in each plane of two horizontal axes
+fn elevation_rings<V: VectorTrait>(elevation: Field) -> Vec<Line<V>> {
+    let center = V::one_hot(UP_AXIS) * (elevation.sin() * SKY_DISTANCE);
+    horizontal_axes::<V>()
+        .into_iter()
+        .tuple_combinations()
+        .flat_map(|(i, j)| {
+            circle(
+                center,
+                V::one_hot(i),
+                V::one_hot(j),
+                elevation.cos() * SKY_DISTANCE,
+            )
+        })
+        .collect()
+}
+
+fn draw_lines<V>(lines: Vec<Line<V>>, color: Color) -> impl Iterator<Item = DrawLine<V>> {
+    lines.into_iter().map(move |line| DrawLine {
+        line,
+        color,
+        end_color: None,
+        style: LineStyle::Solid,
+    })
+}
+
+// rings above the horizon fading from the horizon color to the zenith color,
+// joined by arcs above each horizontal axis that blend between them
+fn sky_gradient<V: VectorTrait>(config: &SkyConfig) -> Vec<DrawLine<V>> {
+    let max_elevation = config.band_elevation.to_radians();
+    let color_at = |elevation: Field| {
+        blend(
+            config.horizon_color,
+            config.zenith_color,
+            (elevation / max_elevation).clamp(0.0, 1.0),
+        )
+    };
+    let rings = (1..=config.bands).flat_map(|band| {
+        let elevation = max_elevation * band as Field / config.bands as Field;
+        draw_lines(elevation_rings(elevation), color_at(elevation))
+    });
+    let arc_segments = SKY_SEGMENTS / 4;
+    let arcs =
+        iproduct!(horizontal_axes::<V>(), [-1.0, 1.0], 0..arc_segments).map(|(axis, sign, s)| {
+            let elevation = |s: usize| max_elevation * s as Field / arc_segments as Field;
+            let point = |s: usize| {
+                (V::one_hot(axis) * (sign * elevation(s).cos())
+                    + V::one_hot(UP_AXIS) * elevation(s).sin())
+                    * SKY_DISTANCE
+            };
+            DrawLine {
+                line: Line(point(s), point(s + 1)),
+                color: color_at(elevation(s)),
+                end_color: Some(color_at(elevation(s + 1))),
+                style: LineStyle::Solid,
+            }
+        });
+    rings.chain(arcs).collect()
+}
+
+// a random unit vector, reproducible from the rng's seed
+fn seeded_sphere_point<V: VectorTrait>(rng: &mut StdRng) -> V {
+    loop {
+        let v = V::from_iter(
+            (0..V::DIM)
+                .map(|_| rng.gen_range(-1.0..1.0))
+                .collect::<Vec<Field>>()
+                .iter(),
+        );
+        let norm = v.norm();
+        if norm > 1e-3 && norm <= 1.0 {
+            return v / norm;
+        }
+    }
+}
+
+// clusters of stars above the horizon, each joined by faint lines from star to nearest star
+fn constellations<V: VectorTrait>(config: &SkyConfig) -> Vec<DrawLine<V>> {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let spread = config.constellation_size.to_radians().tan();
+    let star_size = STAR_SIZE / 2.0;
+    let link_color = config
+        .star_color
+        .set_alpha(config.star_color.0[3] * CONSTELLATION_ALPHA);
+    let mut lines = Vec::new();
+    for _ in 0..config.constellations {
+        let mut center: V = seeded_sphere_point(&mut rng);
+        if center[UP_AXIS] < 0.0 {
+            center = -center;
+        }
+        let mut stars = (0..config.constellation_stars)
+            .map(|_| {
+                let offset = seeded_sphere_point::<V>(&mut rng) * rng.gen::<Field>().sqrt();
+                (center + offset * spread).normalize() * SKY_DISTANCE
+            })
+            .collect_vec();
+        for &star in &stars {
+            lines.extend(draw_lines(
+                (0..V::DIM)
+                    .map(|axis| {
+                        Line(
+                            star - V::one_hot(axis) * star_size,
+                            star + V::one_hot(axis) * star_size,
+                        )
+                    })
+                    .collect(),
+                config.star_color,
+            ));
+        }
+        let mut links = Vec::new();
+        while stars.len() > 1 {
+            let star = stars.pop().unwrap();
+            let (nearest, _) = stars
+                .iter()
+                .enumerate()
+                .map(|(k, &other)| (k, (other - star).norm()))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            // continue the chain from the nearest star
+            let last = stars.len() - 1;
+            stars.swap(nearest, last);
+            links.push(Line(star, stars[last]));
+        }
+        lines.extend(draw_lines(links, link_color));
+    }
+    lines
+}
+
+// circles about the direction of the scene light, one in each plane perpendicular to it
+fn sun<V: VectorTrait>(config: &SkyConfig, light: &LightConfig) -> Vec<DrawLine<V>> {
+    if !(config.sun.enabled && light.enabled) {
+        return Vec::new();
+    }
+    let direction = Light::<V>::from_config(light).direction;
+    // complete the direction to an orthonormal basis
+    let mut basis = vec![direction];
+    for axis in 0..V::DIM {
+        let v = basis
+            .iter()
+            .fold(V::one_hot(axis), |v, &b| v - b * v.dot(b));
+        if v.norm() > 1e-3 {
+            basis.push(v.normalize());
+        }
+    }
+    let center = direction * SKY_DISTANCE;
+    let radius = config.sun.size.to_radians().tan() * SKY_DISTANCE;
+    let lines = basis[1..]
+        .iter()
+        .tuple_combinations()
+        .flat_map(|(&u, &v)| {
+            [radius, radius / 2.0]
+                .into_iter()
+                .flat_map(move |r| circle(center, u, v, r))
+        })
+        .collect();
+    draw_lines(lines, config.sun.color).collect()
+}
+
+fn build_sky<V: VectorTrait>(params: &SkyParams) -> Vec<DrawLine<V>> {
+    let config = &params.config;
+    let mut lines = Vec::new();
+    if params.sky {
+        lines.extend(sky_gradient(config));
+    }
+    if params.horizon {
+        lines.extend(draw_lines(elevation_rings(0.0), config.horizon_color));
+    }
+    if params.stars {
+        lines.extend(constellations(config));
+        lines.extend(draw_stars());
+    }
+    lines.extend(sun(config, &params.light));
+    lines
+}
+
+#[test]
+fn test_sky() {
+    use crate::vector::Vec4;
+    let mut scene = SceneConfig {
+        sky: true,
+        horizon: true,
+        stars: true,
+        ..Default::default()
+    };
+    let mut sky = Sky::<Vec4>::default();
+    assert!(sky.update(&scene, &scene.light));
+    assert!(!sky.update(&scene, &scene.light));
+    // the sky moves with the player, without turning
+    let pos = Vec4::new(1.0, 2.0, -3.0, 4.0);
+    for (line, moved) in sky.lines.iter().zip(sky.lines_around(pos).0) {
+        assert!(Vec4::is_close(line.line.0 + pos, moved.line.0));
+        assert!(Vec4::is_close(line.line.1 + pos, moved.line.1));
+    }
+    // the constellations are the same each time they are built from a seed
+    let stars = constellations::<Vec4>(&scene.sky_dome);
+    for (a, b) in stars.iter().zip(constellations::<Vec4>(&scene.sky_dome)) {
+        assert!(Vec4::is_close(a.line.0, b.line.0));
+    }
+    // changing the scene config rebuilds the sky
+    let n_lines = sky.lines.len();
+    scene.sky_dome.sun.enabled = false;
+    assert!(sky.update(&scene, &scene.light));
+    assert!(sky.lines.len() < n_lines);
+}
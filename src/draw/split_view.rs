@@ -0,0 +1,175 @@
+use crate::components::{Camera, Shape, Transform};
+use crate::config::{DepthFalloff, SplitViewConfig, SplitViewMode, ViewConfig};
+use crate::constants::AXIS_VIEW_COLOR;
+use crate::vector::{Field, MatrixTrait, VecIndex, VectorTrait};
+
+use super::visual_aids::{calc_wireframe_lines, draw_axes};
+use super::{transform_draw_line, DrawLine, DrawLineList, LineStyle, Projection, ViewportShape};
+
+// distance of the axis view cameras from the player
+const AXIS_VIEW_DISTANCE: Field = 100.0;
+const PLAYER_MARKER_LEN: Field = 1.0;
+
+// region of the target, as fractions of its width and height from the bottom left
+#[derive(Clone, Copy, Debug)]
+pub struct ScreenRect {
+    pub left: f32,
+    pub bottom: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+// an orthographic view down one world axis, following the player.
+// shapes are drawn as wireframes without occlusion clipping, so shapes further along the axis
+// show through the ones in front
+pub struct AxisView<V: VectorTrait> {
+    pub axis: VecIndex,
+    pub transform: Transform<V, V::M>,
+    pub camera: Camera<V>,
+    pub view_config: ViewConfig,
+    pub rect: ScreenRect,
+    pub lines: DrawLineList<V::SubV>,
+}
+impl<V: VectorTrait> AxisView<V> {
+    fn new(axis: VecIndex, view_config: ViewConfig, rect: ScreenRect) -> Self {
+        // look down the axis, keeping the remaining axes in order
+        let mut rows: Vec<V> = (0..V::DIM).filter(|&i| i != axis).map(V::one_hot).collect();
+        // keep the frame a rotation rather than a reflection
+        if (V::DIM - axis) % 2 == 1 {
+            rows[0] = rows[0] * -1.0;
+        }
+        rows.push(V::one_hot(axis) * -1.0);
+        let transform = Transform::new(None, Some(V::M::from_vec_of_vecs(&rows)), None);
+        Self {
+            axis,
+            camera: Camera::new(&transform),
+            transform,
+            view_config,
+            rect,
+            lines: DrawLineList(Vec::new()),
+        }
+    }
+    pub fn update<'a, I>(&mut self, player_pos: V, shapes: I)
+    where
+        I: Iterator<Item = &'a Shape<V>>,
+        V: 'a,
+    {
+        self.transform.pos = player_pos + V::one_hot(self.axis) * AXIS_VIEW_DISTANCE;
+        self.camera.update(&self.transform);
+        let wireframe_lines = shapes.flat_map(calc_wireframe_lines).map(|line| DrawLine {
+            line,
+            color: AXIS_VIEW_COLOR,
+            end_color: None,
+            style: LineStyle::Solid,
+        });
+        self.lines.0 = wireframe_lines
+            .chain(draw_axes(player_pos, PLAYER_MARKER_LEN))
+            .flat_map(|draw_line| {
+                transform_draw_line(draw_line, &self.transform, &self.camera, &self.view_config)
+            })
+            .collect();
+    }
+}
+
+// orthographic views of the whole level, drawn over the main view
+pub struct SplitView<V: VectorTrait> {
+    // the split view and main view configs the views were built from
+    built_from: Option<(SplitViewConfig, ViewConfig)>,
+    pub views: Vec<AxisView<V>>,
+}
+impl<V: VectorTrait> Default for SplitView<V> {
+    fn default() -> Self {
+        Self {
+            built_from: None,
+            views: Vec::new(),
+        }
+    }
+}
+impl<V: VectorTrait> SplitView<V> {
+    // rebuilds the views when either config has changed since they were built
+    pub fn set_config(&mut self, config: &SplitViewConfig, main_view_config: &ViewConfig) {
+        let built_from = (config.clone(), main_view_config.clone());
+        if self.built_from.as_ref() == Some(&built_from) {
+            return;
+        }
+        self.built_from = Some(built_from);
+        let mut view_config = ViewConfig {
+            viewport_shape: ViewportShape::Cube,
+            projection: Projection::Orthographic,
+            focal: main_view_config.radius / config.view_radius,
+            ..main_view_config.clone()
+        };
+        view_config.depth_cue.falloff = DepthFalloff::None;
+        view_config.hidden_axis.enabled = false;
+        let size = config.size;
+        self.views = match config.mode {
+            SplitViewMode::Off => Vec::new(),
+            SplitViewMode::AxisViews => {
+                // stacked down the right edge of the screen, first axis at the top
+                let height = 1.0 / V::DIM as f32;
+                (0..V::DIM)
+                    .map(|axis| {
+                        let rect = ScreenRect {
+                            left: 1.0 - size,
+                            bottom: (V::DIM - 1 - axis) as f32 * height,
+                            width: size,
+                            height,
+                        };
+                        AxisView::new(axis, view_config.clone(), rect)
+                    })
+                    .collect()
+            }
+            SplitViewMode::Map => {
+                let rect = ScreenRect {
+                    left: 1.0 - size,
+                    bottom: 1.0 - size,
+                    width: size,
+                    height: size,
+                };
+                vec![AxisView::new(1, view_config, rect)]
+            }
+        };
+    }
+}
+
+#[test]
+fn test_map_view() {
+    use crate::config::Config;
+    use crate::vector::{Vec2, Vec3};
+
+    let config = Config::default();
+    let mut split_view = SplitView::<Vec3>::default();
+    split_view.set_config(
+        &SplitViewConfig {
+            mode: SplitViewMode::Map,
+            ..Default::default()
+        },
+        &config.view,
+    );
+    let player_pos = Vec3::new(3.0, 2.0, -5.0);
+    let map_view = &mut split_view.views[0];
+    map_view.update(player_pos, std::iter::empty());
+    // the player marker: x runs across the map, the vertical axis points at the camera and z runs up the map
+    let scale = map_view.view_config.focal * PLAYER_MARKER_LEN;
+    let marker: Vec<_> = map_view.lines.0.iter().map(|l| &l.line).collect();
+    assert!(Vec2::is_close(marker[0].1, Vec2::new(scale, 0.0)));
+    assert!(Vec2::is_close(marker[1].0, marker[1].1));
+    assert!(Vec2::is_close(marker[2].1, Vec2::new(0.0, scale)));
+
+    // changing the config rebuilds the views, even in the same mode
+    let focal = split_view.views[0].view_config.focal;
+    let wider = SplitViewConfig {
+        mode: SplitViewMode::Map,
+        view_radius: SplitViewConfig::default().view_radius * 2.0,
+        ..Default::default()
+    };
+    split_view.set_config(&wider, &config.view);
+    assert!((split_view.views[0].view_config.focal - focal / 2.0).abs() < 1e-5);
+    let mut view_config = config.view.clone();
+    view_config.lines.width_3d *= 2.0;
+    split_view.set_config(&wider, &view_config);
+    assert_eq!(
+        split_view.views[0].view_config.lines.width_3d,
+        view_config.lines.width_3d
+    );
+}
@@ -1,23 +1,36 @@
 use std::marker::PhantomData;
 
-use specs::{Entities, Join, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
+use specs::{
+    Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, Write, WriteExpect, WriteStorage,
+};
 
 use crate::{
+    collide::get_entities_in_bbox,
     components::{
-        BBall, Camera, ClipState, Cursor, Player, Shape, ShapeClipState, ShapeType, Transform,
+        BBall, Camera, ClipState, Cursor, Player, Shape, ShapeClipState, ShapeType, StaticCollider,
+        Transform,
     },
     config::Config,
     constants::FACE_SCALE,
     ecs_utils::{Componentable, SystemName},
+    spatial_hash::SpatialHashSet,
     vector::{Field, VectorTrait},
 };
 
 use super::{
-    calc_shapes_lines,
-    clipping::{calc_in_front, calc_in_front_pair, InFrontArg},
+    bball_culled, calc_shapes_lines,
+    clipping::{bsp::BspTree, calc_in_front},
     draw_cursor,
     draw_line_collection::{draw_collection, DrawLineCollection},
-    transform_draw_line, update_shape_visibility, DrawLine, DrawLineList, ShapeTexture,
+    fill::{calc_shape_fills, viewport_fill_planes, DrawTriangleList},
+    light::{LevelLight, Light},
+    line_cache::ShapeLinesCache,
+    shadow::{cast_shadow, shadow_column, shadow_marker, DropShadow},
+    sky::Sky,
+    split_view::SplitView,
+    transform_draw_line, update_shape_visibility,
+    viewport::viewport_outline,
+    DrawLine, DrawLineList, ShapeTexture, ShapesCulled,
 };
 
 //would be nicer to move lines out of read_in_lines rather than clone them
@@ -54,12 +67,44 @@ where
                 )
             })
             .collect();
+        if config.view.viewport_outline {
+            write_out_lines
+                .0
+                .extend(viewport_outline::<V::SubV>(&config.view));
+        }
     }
 }
 impl SystemName for TransformDrawLinesSystem<()> {
     const NAME: &'static str = "transform_draw_lines";
 }
 
+pub struct SplitViewSystem<V>(pub PhantomData<V>);
+impl<'a, V> System<'a> for SplitViewSystem<V>
+where
+    V: VectorTrait + Componentable,
+    V::SubV: Componentable,
+    V::M: Componentable,
+{
+    type SystemData = (
+        ReadStorage<'a, Shape<V>>,
+        ReadStorage<'a, Transform<V, V::M>>,
+        ReadExpect<'a, Player>,
+        ReadExpect<'a, Config>,
+        Write<'a, SplitView<V>>,
+    );
+
+    fn run(&mut self, (shapes, transform, player, config, mut split_view): Self::SystemData) {
+        split_view.set_config(&config.split_view, &config.view);
+        let player_pos = transform.get(player.0).unwrap().pos;
+        for view in split_view.views.iter_mut() {
+            view.update(player_pos, shapes.join());
+        }
+    }
+}
+impl SystemName for SplitViewSystem<()> {
+    const NAME: &'static str = "split_view";
+}
+
 pub struct DrawCursorSystem<V>(pub PhantomData<V>);
 impl<'a, V> System<'a> for DrawCursorSystem<V>
 where
@@ -83,6 +128,107 @@ impl SystemName for DrawCursorSystem<()> {
     const NAME: &'static str = "draw_cursor";
 }
 
+//mark shapes whose bounding ball is out of view, so later draw steps can skip them
+pub struct CullingSystem<V>(pub PhantomData<V>);
+
+impl<'a, V> System<'a> for CullingSystem<V>
+where
+    V: VectorTrait + Componentable,
+    V::M: Componentable,
+{
+    type SystemData = (
+        ReadStorage<'a, BBall<V>>,
+        WriteStorage<'a, ShapeClipState<V>>,
+        ReadStorage<'a, Camera<V>>,
+        ReadStorage<'a, Transform<V, V::M>>,
+        ReadExpect<'a, Player>,
+        ReadExpect<'a, Config>,
+        Write<'a, ShapesCulled>,
+    );
+
+    fn run(
+        &mut self,
+        (bballs, mut shape_clip_states, camera, transform, player, config, mut shapes_culled): Self::SystemData,
+    ) {
+        let transform = transform.get(player.0).unwrap();
+        let camera = camera.get(player.0).unwrap();
+        shapes_culled.0 = 0;
+        for (bball, shape_clip_state) in (&bballs, &mut shape_clip_states).join() {
+            shape_clip_state.culled = bball_culled(bball, transform, camera, &config.view);
+            if shape_clip_state.culled {
+                shapes_culled.0 += 1;
+            }
+        }
+    }
+}
+impl SystemName for CullingSystem<()> {
+    const NAME: &'static str = "culling";
+}
+
+pub struct LevelOfDetailSystem<V>(pub PhantomData<V>);
+
+impl<'a, V> System<'a> for LevelOfDetailSystem<V>
+where
+    V: VectorTrait + Componentable,
+    V::M: Componentable,
+{
+    type SystemData = (
+        ReadStorage<'a, BBall<V>>,
+        WriteStorage<'a, ShapeClipState<V>>,
+        ReadStorage<'a, Transform<V, V::M>>,
+        ReadExpect<'a, Player>,
+        ReadExpect<'a, Config>,
+    );
+
+    fn run(
+        &mut self,
+        (bballs, mut shape_clip_states, transform, player, config): Self::SystemData,
+    ) {
+        let camera_pos = transform.get(player.0).unwrap().pos;
+        for (bball, shape_clip_state) in (&bballs, &mut shape_clip_states)
+            .join()
+            .filter(|(_, shape_clip_state)| !shape_clip_state.culled)
+        {
+            shape_clip_state.detail = config.lod.enabled.then(|| {
+                let distance = ((bball.pos - camera_pos).norm() - bball.radius).max(0.0);
+                config.lod.detail(distance)
+            });
+        }
+    }
+}
+impl SystemName for LevelOfDetailSystem<()> {
+    const NAME: &'static str = "level_of_detail";
+}
+
+pub struct LightingSystem<V>(pub PhantomData<V>);
+
+impl<'a, V> System<'a> for LightingSystem<V>
+where
+    V: VectorTrait + Componentable,
+{
+    type SystemData = (
+        ReadStorage<'a, Shape<V>>,
+        WriteStorage<'a, ShapeClipState<V>>,
+        Read<'a, Light<V>>,
+    );
+
+    fn run(&mut self, (shapes, mut shape_clip_states, light): Self::SystemData) {
+        for (shape, shape_clip_state) in (&shapes, &mut shape_clip_states)
+            .join()
+            .filter(|(_, shape_clip_state)| !shape_clip_state.culled)
+        {
+            shape_clip_state.face_brightness = shape
+                .faces
+                .iter()
+                .map(|face| light.brightness(face.normal()))
+                .collect();
+        }
+    }
+}
+impl SystemName for LightingSystem<()> {
+    const NAME: &'static str = "lighting";
+}
+
 //in this implementation, the length of the vec is always
 //the same, and invisible faces are just sequences of None
 //seems to be significantly slower than not padding and just changing the buffer when needed
@@ -131,6 +277,68 @@ impl SystemName for VisibilitySystem<()> {
     const NAME: &'static str = "visibility";
 }
 
+pub struct CalcFillsSystem<V>(pub PhantomData<V>);
+
+impl<'a, V> System<'a> for CalcFillsSystem<V>
+where
+    V: VectorTrait + Componentable,
+    V::SubV: Componentable,
+    V::M: Componentable,
+{
+    type SystemData = (
+        ReadStorage<'a, Shape<V>>,
+        ReadStorage<'a, ShapeTexture<V::SubV>>,
+        ReadStorage<'a, ShapeClipState<V>>,
+        ReadStorage<'a, Camera<V>>,
+        ReadStorage<'a, Transform<V, V::M>>,
+        ReadExpect<'a, Player>,
+        ReadExpect<'a, Config>,
+        Write<'a, DrawTriangleList<V::SubV>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            shapes,
+            shape_textures,
+            shape_clip_states,
+            camera,
+            transform,
+            player,
+            config,
+            mut triangles,
+        ): Self::SystemData,
+    ) {
+        triangles.0.clear();
+        if !config.view.fill.enabled {
+            return;
+        }
+        let Some(viewport_planes) = viewport_fill_planes(&config.view) else {
+            return;
+        };
+        let transform = transform.get(player.0).unwrap();
+        let camera = camera.get(player.0).unwrap();
+        for (shape, shape_texture, shape_clip_state) in
+            (&shapes, &shape_textures, &shape_clip_states)
+                .join()
+                .filter(|(_, _, shape_clip_state)| !shape_clip_state.culled)
+        {
+            triangles.0.extend(calc_shape_fills(
+                shape,
+                shape_texture,
+                shape_clip_state,
+                transform,
+                camera,
+                &config.view,
+                &viewport_planes,
+            ));
+        }
+    }
+}
+impl SystemName for CalcFillsSystem<()> {
+    const NAME: &'static str = "calc_fills";
+}
+
 pub struct CalcShapesLinesSystem<V>(pub PhantomData<V>);
 
 impl<'a, V> System<'a> for CalcShapesLinesSystem<V>
@@ -143,20 +351,32 @@ where
         ReadStorage<'a, Shape<V>>,
         ReadStorage<'a, ShapeTexture<V::SubV>>,
         ReadStorage<'a, ShapeClipState<V>>,
+        WriteStorage<'a, ShapeLinesCache<V>>,
         ReadExpect<'a, ClipState<V>>,
+        ReadExpect<'a, Config>,
         WriteExpect<'a, DrawLineList<V>>, // TODO: break up into components so that these can be processed more in parallel with par_iter?
     );
 
     fn run(
         &mut self,
-        (shapes, shape_textures, shape_clip_states, clip_state, mut lines): Self::SystemData,
+        (
+            shapes,
+            shape_textures,
+            shape_clip_states,
+            mut shape_lines_caches,
+            clip_state,
+            config,
+            mut lines,
+        ): Self::SystemData,
     ) {
         lines.0 = calc_shapes_lines(
             &shapes,
             &shape_textures,
             &shape_clip_states,
+            &mut shape_lines_caches,
             &[FACE_SCALE],
             &clip_state,
+            config.view.ghost.enabled.then_some(&config.view.ghost),
         );
     }
 }
@@ -177,17 +397,19 @@ where
         Entities<'a>,
         ReadStorage<'a, Transform<V, V::M>>,
         ReadExpect<'a, Player>,
+        Read<'a, BspTree<V, Entity>>,
     );
 
     fn run(
         &mut self,
-        (shape_data, bball_data, mut shape_clip_state,entities,transform,player) : Self::SystemData,
+        (shape_data, bball_data, mut shape_clip_state,entities,transform,player,bsp_tree) : Self::SystemData,
     ) {
         calc_in_front(
             &shape_data,
             &bball_data,
             &mut shape_clip_state,
             &entities,
+            &bsp_tree,
             &transform.get(player.0).unwrap().pos,
         );
     }
@@ -204,22 +426,33 @@ where
 {
     type SystemData = (
         ReadStorage<'a, DrawLineCollection<V>>,
+        ReadStorage<'a, DropShadow<V>>,
         ReadStorage<'a, ShapeClipState<V>>,
         ReadExpect<'a, ClipState<V>>,
+        ReadExpect<'a, Config>,
         WriteExpect<'a, DrawLineList<V>>, // TODO: break up into components so that these can be processed more in parallel with par_iter?
     );
 
     // TODO: this will clip using ALL shapes. is there a way to reduce the workload?
     fn run(
         &mut self,
-        (line_collection_storage, read_shape_clip_state, clip_state, mut lines): Self::SystemData,
+        (
+            line_collection_storage,
+            drop_shadows,
+            read_shape_clip_state,
+            clip_state,
+            config,
+            mut lines,
+        ): Self::SystemData,
     ) {
-        for lines_coll in line_collection_storage.join() {
+        let markers = drop_shadows.join().map(|drop_shadow| &drop_shadow.marker);
+        for lines_coll in line_collection_storage.join().chain(markers) {
             lines.0.extend(draw_collection(
                 lines_coll,
                 clip_state
                     .clipping_enabled
                     .then_some((&read_shape_clip_state).join()),
+                config.view.ghost.enabled.then_some(&config.view.ghost),
             ));
         }
     }
@@ -227,3 +460,96 @@ where
 impl SystemName for DrawLineCollectionSystem<()> {
     const NAME: &'static str = "line_collection_system";
 }
+
+pub struct SkySystem<V>(pub PhantomData<V>);
+
+impl<'a, V> System<'a> for SkySystem<V>
+where
+    V: VectorTrait + Componentable,
+    V::M: Componentable,
+{
+    type SystemData = (
+        Write<'a, Sky<V>>,
+        ReadStorage<'a, Transform<V, V::M>>,
+        ReadStorage<'a, ShapeClipState<V>>,
+        ReadExpect<'a, Player>,
+        ReadExpect<'a, ClipState<V>>,
+        ReadExpect<'a, Config>,
+        ReadExpect<'a, LevelLight>,
+        WriteExpect<'a, DrawLineList<V>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            mut sky,
+            transforms,
+            read_shape_clip_state,
+            player,
+            clip_state,
+            config,
+            level_light,
+            mut lines,
+        ): Self::SystemData,
+    ) {
+        sky.update(&config.scene, &level_light.0);
+        let player_pos = transforms.get(player.0).unwrap().pos;
+        // the sky is hidden behind shapes, even in ghost mode
+        lines.0.extend(draw_collection(
+            &sky.lines_around(player_pos),
+            clip_state
+                .clipping_enabled
+                .then_some((&read_shape_clip_state).join()),
+            None,
+        ));
+    }
+}
+impl SystemName for SkySystem<()> {
+    const NAME: &'static str = "sky";
+}
+
+pub struct DropShadowSystem<V>(pub PhantomData<V>);
+
+impl<'a, V> System<'a> for DropShadowSystem<V>
+where
+    V: VectorTrait + Componentable,
+    V::M: Componentable,
+{
+    type SystemData = (
+        WriteStorage<'a, DropShadow<V>>,
+        ReadStorage<'a, Transform<V, V::M>>,
+        ReadStorage<'a, Shape<V>>,
+        ReadStorage<'a, ShapeType<V>>,
+        ReadStorage<'a, StaticCollider>,
+        ReadExpect<'a, SpatialHashSet<V, Entity>>,
+        ReadExpect<'a, Config>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            mut drop_shadows,
+            transforms,
+            shapes,
+            shape_types,
+            static_colliders,
+            hash,
+            config,
+        ): Self::SystemData,
+    ) {
+        for (drop_shadow, transform) in (&mut drop_shadows, &transforms).join() {
+            // only the colliders in the cells below the entity can be hit
+            let colliders = get_entities_in_bbox(&shadow_column(transform.pos), &hash)
+                .into_iter()
+                .filter(|&e| static_colliders.contains(e))
+                .filter_map(|e| Some((shapes.get(e)?, shape_types.get(e)?)));
+            drop_shadow.marker = match cast_shadow(transform.pos, colliders) {
+                Some(point) => shadow_marker(point, config.scene.shadows.size),
+                None => DrawLineCollection(Vec::new()),
+            };
+        }
+    }
+}
+impl SystemName for DropShadowSystem<()> {
+    const NAME: &'static str = "drop_shadow";
+}
@@ -1,7 +1,11 @@
+pub mod library;
+pub mod procedural;
+pub mod text;
+
 use super::visual_aids::random_sphere_point;
-use super::DrawLine;
+use super::{DrawLine, LineStyle};
 
-use crate::constants::{CARDINAL_COLORS, FACE_SCALE};
+use crate::constants::{CARDINAL_COLORS, FACE_SCALE, LOD_FADE};
 use crate::geometry::{
     shape::{Edge, Face, Shape, VertIndex},
     Line,
@@ -24,12 +28,20 @@ impl<U> ShapeTexture<U> {
             face_textures: (0..n_faces).map(|_| Default::default()).collect(),
         }
     }
+    // replaces the colors of every face, including any per-line colors
     pub fn with_color(mut self, color: Color) -> Self {
         for face in &mut self.face_textures {
             face.set_color(color);
         }
         self
     }
+    // multiplies the colors of every face, keeping patterns of colors
+    pub fn with_tint(mut self, tint: Color) -> Self {
+        for face in &mut self.face_textures {
+            take_mut::take(&mut face.texture, |tex| tex.tint(tint));
+        }
+        self
+    }
 }
 impl<U: Clone> ShapeTexture<U> {
     pub fn with_texture(mut self, face_texture: FaceTexture<U>) -> Self {
@@ -90,23 +102,85 @@ pub fn draw_face_texture<V: VectorTrait>(
     }
     match &face_texture.texture {
         Texture::DefaultLines { color } => draw_default_lines(face, shape, *color, face_scales),
-        Texture::Lines { lines, color } => face_texture
-            .texture_mapping
-            .as_ref()
-            .unwrap()
-            .draw_lines(shape, lines, *color),
+        Texture::Lines {
+            lines,
+            color,
+            colors,
+            ..
+        } => face_texture.texture_mapping.as_ref().unwrap().draw_lines(
+            shape,
+            lines,
+            *color,
+            colors.as_deref(),
+        ),
         Texture::DrawLines(draw_lines) => face_texture
             .texture_mapping
             .as_ref()
             .unwrap()
-            .draw_drawlines(draw_lines),
+            .draw_drawlines(shape, draw_lines),
     }
 }
 
+// range of detail levels over which a texture line is drawn.
+// the line fades in over LOD_FADE above min, and fades out over LOD_FADE above max
+#[derive(Clone, Copy, Debug)]
+pub struct DetailRange {
+    pub min: Field,
+    pub max: Field,
+}
+impl Default for DetailRange {
+    fn default() -> Self {
+        Self {
+            min: Field::NEG_INFINITY,
+            max: Field::INFINITY,
+        }
+    }
+}
+impl DetailRange {
+    pub fn alpha(&self, detail: Field) -> Field {
+        let fade_in = (detail - self.min) / LOD_FADE;
+        let fade_out = (self.max + LOD_FADE - detail) / LOD_FADE;
+        fade_in.min(fade_out).clamp(0.0, 1.0)
+    }
+}
+
+// fade out lines outside the given level of detail
+pub fn lines_at_detail<'a, V: Clone>(
+    lines: &'a [DrawLine<V>],
+    detail_ranges: &'a [DetailRange],
+    detail: Field,
+) -> impl Iterator<Item = DrawLine<V>> + 'a {
+    lines
+        .iter()
+        .zip(detail_ranges)
+        .filter_map(move |(draw_line, range)| {
+            let alpha = range.alpha(detail);
+            (alpha > 0.0).then(|| DrawLine {
+                line: draw_line.line.clone(),
+                color: draw_line
+                    .color
+                    .set_alpha(draw_line.color.get_arr()[3] * alpha),
+                end_color: draw_line
+                    .end_color
+                    .map(|color| color.set_alpha(color.get_arr()[3] * alpha)),
+                style: draw_line.style,
+            })
+        })
+}
+
 #[derive(Clone)]
 pub enum Texture<V> {
-    DefaultLines { color: Color },
-    Lines { lines: Vec<Line<V>>, color: Color },
+    DefaultLines {
+        color: Color,
+    },
+    Lines {
+        lines: Vec<Line<V>>,
+        color: Color,
+        // detail range of each line. None if the lines are drawn at every level of detail
+        detail: Option<Vec<DetailRange>>,
+        // color of each line. None if every line is drawn in color
+        colors: Option<Vec<Color>>,
+    },
     DrawLines(Vec<DrawLine<V>>), // I don't remember what this one is for
 }
 impl<V> Default for Texture<V> {
@@ -115,28 +189,122 @@ impl<V> Default for Texture<V> {
     }
 }
 impl<V> Texture<V> {
+    pub fn color(&self) -> Color {
+        match self {
+            Texture::DefaultLines { color } | Texture::Lines { color, .. } => *color,
+            Texture::DrawLines(draw_lines) => draw_lines
+                .first()
+                .map(|draw_line| draw_line.color)
+                .unwrap_or(DEFAULT_COLOR),
+        }
+    }
     pub fn set_color(self, color: Color) -> Self {
         match self {
             Texture::DefaultLines { .. } => Texture::DefaultLines { color },
-            Texture::Lines { lines, .. } => Texture::Lines { lines, color },
+            Texture::Lines { lines, detail, .. } => Texture::Lines {
+                lines,
+                color,
+                detail,
+                colors: None,
+            },
             Texture::DrawLines(draw_lines) => Texture::DrawLines(
                 draw_lines
                     .into_iter()
                     .map(|draw_line| DrawLine {
                         line: draw_line.line,
                         color,
+                        end_color: None,
+                        style: draw_line.style,
                     })
                     .collect(),
             ),
         }
     }
+    pub fn tint(self, tint: Color) -> Self {
+        match self {
+            Texture::DefaultLines { color } => Texture::DefaultLines {
+                color: color.tint(tint),
+            },
+            Texture::Lines {
+                lines,
+                color,
+                detail,
+                colors,
+            } => Texture::Lines {
+                lines,
+                color: color.tint(tint),
+                detail,
+                colors: colors.map(|colors| colors.into_iter().map(|c| c.tint(tint)).collect()),
+            },
+            Texture::DrawLines(draw_lines) => Texture::DrawLines(
+                draw_lines
+                    .into_iter()
+                    .map(|draw_line| DrawLine {
+                        color: draw_line.color.tint(tint),
+                        end_color: draw_line.end_color.map(|c| c.tint(tint)),
+                        ..draw_line
+                    })
+                    .collect(),
+            ),
+        }
+    }
+    pub fn detail(&self) -> Option<&[DetailRange]> {
+        match self {
+            Texture::Lines {
+                detail: Some(detail),
+                ..
+            } => Some(detail),
+            _ => None,
+        }
+    }
 }
 impl<V: VectorTrait> Texture<V> {
     pub fn make_single_tile_texture(color: Color) -> Self {
         Texture::make_tile_texture(&[FACE_SCALE], &(0..V::DIM).map(|_| 1).collect_vec())
             .set_color(color)
     }
+    // tiles are subdivided less at lower levels of detail, halving the divisions at each level.
+    // each level is drawn for detail in (0.5^(level + 1), 0.5^level], crossfading with the next
     pub fn make_tile_texture(scales: &[Field], n_divisions: &Vec<i32>) -> Self {
+        let mut levels = vec![n_divisions.clone()];
+        while levels.last().unwrap().iter().any(|&n| n > 1) {
+            let coarser = levels.last().unwrap().iter().map(|n| (n + 1) / 2).collect();
+            levels.push(coarser);
+        }
+        let n_levels = levels.len();
+        if n_levels == 1 {
+            return Texture::Lines {
+                lines: Self::make_tile_lines(scales, n_divisions),
+                color: DEFAULT_COLOR,
+                detail: None,
+                colors: None,
+            };
+        }
+        let mut lines = Vec::new();
+        let mut detail = Vec::new();
+        for (level, divisions) in levels.iter().enumerate() {
+            let level_lines = Self::make_tile_lines(scales, divisions);
+            let range = DetailRange {
+                min: match level + 1 == n_levels {
+                    true => Field::NEG_INFINITY,
+                    false => (0.5 as Field).powi(level as i32 + 1),
+                },
+                max: match level {
+                    0 => Field::INFINITY,
+                    _ => (0.5 as Field).powi(level as i32),
+                },
+            };
+            detail.extend(level_lines.iter().map(|_| range));
+            lines.extend(level_lines);
+        }
+        Texture::Lines {
+            lines,
+            color: DEFAULT_COLOR,
+            detail: Some(detail),
+            colors: None,
+        }
+    }
+    fn make_tile_lines(scales: &[Field], n_divisions: &Vec<i32>) -> Vec<Line<V>> {
         if V::DIM != n_divisions.len() as VecIndex {
             panic!(
                 "make_tile_texture: Expected n_divisions.len()={} but got {}",
@@ -192,23 +360,95 @@ impl<V: VectorTrait> Texture<V> {
             }
         }
 
-        let lines = centers
+        centers
             .cartesian_product(scales.iter())
             .flat_map(|(center, &scale)| {
                 tile_lines
                     .iter()
                     .map(move |line| line.map(|v| v * scale + center))
             })
-            .collect();
-        Texture::Lines {
-            lines,
-            color: DEFAULT_COLOR,
-        }
+            .collect()
     }
+    // points are randomly placed, so dropping points from the end thins the fuzz out evenly
     pub fn make_fuzz_texture(n: usize) -> Self {
         Texture::Lines {
             lines: (0..n).map(|_| pointlike_line(V::random())).collect(),
             color: DEFAULT_COLOR,
+            detail: Some(fuzz_detail(n)),
+            colors: None,
+        }
+    }
+    // blends the color of each line towards end_color along the axis of texture space,
+    // by the position of the line's midpoint. lines keep their own colors at the start of the axis
+    pub fn with_gradient(self, axis: VecIndex, end_color: Color) -> Self {
+        match self {
+            Texture::DefaultLines { color } => {
+                Texture::make_single_tile_texture(color).with_gradient(axis, end_color)
+            }
+            Texture::Lines {
+                lines,
+                color,
+                detail,
+                colors,
+            } => Texture::Lines {
+                colors: Some(
+                    lines
+                        .iter()
+                        .enumerate()
+                        .map(|(i, line)| {
+                            let start_color = colors.as_ref().map_or(color, |colors| colors[i]);
+                            let t = line.linterp(0.5)[axis].clamp(0.0, 1.0);
+                            blend(start_color, end_color, t)
+                        })
+                        .collect(),
+                ),
+                lines,
+                color,
+                detail,
+            },
+            // each end of a draw line is blended by its own position
+            Texture::DrawLines(draw_lines) => Texture::DrawLines(
+                draw_lines
+                    .into_iter()
+                    .map(|draw_line| {
+                        let t = |p: V| p[axis].clamp(0.0, 1.0);
+                        DrawLine {
+                            color: blend(draw_line.color, end_color, t(draw_line.line.0)),
+                            end_color: Some(blend(
+                                draw_line.end_color.unwrap_or(draw_line.color),
+                                end_color,
+                                t(draw_line.line.1),
+                            )),
+                            ..draw_line
+                        }
+                    })
+                    .collect(),
+            ),
+        }
+    }
+    // the lines of the texture as draw lines, each in its own color.
+    // draw lines have no detail range, so they are drawn at every level of detail
+    fn draw_lines(&self) -> Vec<DrawLine<V>> {
+        match self {
+            Texture::DefaultLines { color } => {
+                Texture::make_single_tile_texture(*color).draw_lines()
+            }
+            Texture::Lines {
+                lines,
+                color,
+                colors,
+                ..
+            } => lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| DrawLine {
+                    line: line.clone(),
+                    color: colors.as_ref().map_or(*color, |colors| colors[i]),
+                    end_color: None,
+                    style: LineStyle::Solid,
+                })
+                .collect(),
+            Texture::DrawLines(draw_lines) => draw_lines.clone(),
         }
     }
     pub fn merged_with(&self, texture: &Texture<V>) -> Texture<V> {
@@ -222,22 +462,71 @@ impl<V: VectorTrait> Texture<V> {
             (
                 Texture::Lines {
                     lines: lines_1,
-                    color,
+                    color: color_1,
+                    detail: detail_1,
+                    colors: colors_1,
+                },
+                Texture::Lines {
+                    lines: lines_2,
+                    color: color_2,
+                    detail: detail_2,
+                    colors: colors_2,
                 },
-                Texture::Lines { lines: lines_2, .. },
             ) => Texture::Lines {
                 lines: {
                     let mut lines = lines_1.clone();
                     lines.extend(lines_2.clone());
                     lines
                 },
-                color: *color,
+                color: *color_1,
+                // each side keeps its colors
+                colors: match (colors_1, colors_2) {
+                    (None, None) if color_1 == color_2 => None,
+                    _ => {
+                        let line_colors = |colors: &Option<Vec<Color>>, color: Color, n: usize| {
+                            colors.clone().unwrap_or_else(|| vec![color; n])
+                        };
+                        let mut colors = line_colors(colors_1, *color_1, lines_1.len());
+                        colors.extend(line_colors(colors_2, *color_2, lines_2.len()));
+                        Some(colors)
+                    }
+                },
+                detail: match (detail_1, detail_2) {
+                    (None, None) => None,
+                    _ => {
+                        let full_detail = |n: usize| vec![DetailRange::default(); n];
+                        let mut detail = detail_1
+                            .clone()
+                            .unwrap_or_else(|| full_detail(lines_1.len()));
+                        detail.extend(
+                            detail_2
+                                .clone()
+                                .unwrap_or_else(|| full_detail(lines_2.len())),
+                        );
+                        Some(detail)
+                    }
+                },
             },
-            _ => panic!("Unsupported texture merge operation"),
+            // merging with draw lines gives draw lines
+            (Texture::DrawLines(_), _) | (_, Texture::DrawLines(_)) => Texture::DrawLines({
+                let mut draw_lines = self.draw_lines();
+                draw_lines.extend(texture.draw_lines());
+                draw_lines
+            }),
         }
     }
 }
 
+// the ith of n fuzz points fades out at detail i / n
+fn fuzz_detail(n: usize) -> Vec<DetailRange> {
+    (0..n)
+        .map(|i| DetailRange {
+            min: (i as Field) / (n as Field) * (1.0 - LOD_FADE),
+            max: Field::INFINITY,
+        })
+        .collect()
+}
+
 #[derive(Clone)]
 pub struct TextureMapping {
     pub frame_vertis: Vec<VertIndex>,
@@ -245,12 +534,8 @@ pub struct TextureMapping {
 }
 
 impl TextureMapping {
-    pub fn draw_lines<V: VectorTrait>(
-        &self,
-        shape: &Shape<V>,
-        lines: &[Line<V::SubV>],
-        color: Color,
-    ) -> Vec<DrawLine<V>> {
+    // maps texture space onto the face
+    fn map_line<V: VectorTrait>(&self, shape: &Shape<V>) -> impl Fn(&Line<V::SubV>) -> Line<V> {
         let origin = shape.verts[self.origin_verti];
         let frame_verts: Vec<V> = self
             .frame_vertis
@@ -260,26 +545,51 @@ impl TextureMapping {
         //this is pretty ridiculous. it just matrix multiplies a matrix of frame_verts (as columns) by each vertex
         //in every line, then adds the origin.
         //TODO: a lot of time is spent doing this calculation
+        move |line| {
+            line.map(|v| {
+                (0..V::SubV::DIM)
+                    .zip(frame_verts.iter())
+                    .map(|(i, &f)| f * v[i])
+                    .fold(V::zero(), |a, b| a + b)
+                    + origin
+            })
+        }
+    }
+    // colors, if given, has the color of each line
+    pub fn draw_lines<V: VectorTrait>(
+        &self,
+        shape: &Shape<V>,
+        lines: &[Line<V::SubV>],
+        color: Color,
+        colors: Option<&[Color]>,
+    ) -> Vec<DrawLine<V>> {
+        let map_line = self.map_line(shape);
         lines
             .iter()
-            .map(|line| {
-                line.map(|v| {
-                    (0..V::SubV::DIM)
-                        .zip(frame_verts.iter())
-                        .map(|(i, &f)| f * v[i])
-                        .fold(V::zero(), |a, b| a + b)
-                        + origin
-                })
+            .enumerate()
+            .map(|(i, line)| DrawLine {
+                line: map_line(line),
+                color: colors.map_or(color, |colors| colors[i]),
+                end_color: None,
+                style: LineStyle::Solid,
             })
-            .map(|line| DrawLine { line, color })
             .collect()
     }
     pub fn draw_drawlines<V: VectorTrait>(
         &self,
-        _draw_lines: &[DrawLine<V::SubV>],
+        shape: &Shape<V>,
+        draw_lines: &[DrawLine<V::SubV>],
     ) -> Vec<DrawLine<V>> {
-        unimplemented!()
-        //draw_lines.iter().map(|draw_line| Some(draw_line.clone())).collect()
+        let map_line = self.map_line(shape);
+        draw_lines
+            .iter()
+            .map(|draw_line| DrawLine {
+                line: map_line(&draw_line.line),
+                color: draw_line.color,
+                end_color: draw_line.end_color,
+                style: draw_line.style,
+            })
+            .collect()
     }
     //use face edges and reference vertices to determine vertex indices for texture mapping
     //order by side length, in decreasing order
@@ -325,6 +635,8 @@ pub fn draw_default_lines<V: VectorTrait>(
             lines.push(DrawLine {
                 line: Line(shape.verts[edge.0], shape.verts[edge.1]).map(scale_point),
                 color,
+                end_color: None,
+                style: LineStyle::Solid,
             });
         }
     }
@@ -396,3 +708,80 @@ pub fn color_duocylinder<V: VectorTrait>(
 pub fn pointlike_line<V: VectorTrait>(pos: V) -> Line<V> {
     Line(pos, pos + random_sphere_point::<V>() * 0.005)
 }
+
+#[test]
+fn test_merge_keeps_colors() {
+    use crate::vector::Vec2;
+    let red_tiles = Texture::<Vec2>::make_tile_texture(&[FACE_SCALE], &vec![1, 1]).set_color(RED);
+    let blue_fuzz = Texture::<Vec2>::make_fuzz_texture(3).set_color(BLUE);
+    let merged = red_tiles
+        .merged_with(&blue_fuzz)
+        .with_gradient(0, WHITE)
+        .tint(GRAY);
+    let Texture::Lines {
+        lines,
+        colors: Some(colors),
+        ..
+    } = merged
+    else {
+        panic!("merged texture should have per-line colors")
+    };
+    assert_eq!(colors.len(), lines.len());
+    // the tile's edges stay red, blended towards white from left to right and tinted gray
+    for (line, color) in lines.iter().zip(&colors).take(4) {
+        let expected = blend(RED, WHITE, line.linterp(0.5)[0]).tint(GRAY);
+        assert!(color
+            .0
+            .iter()
+            .zip(expected.0)
+            .all(|(a, b)| (a - b).abs() < 1e-5));
+    }
+    // fuzz is still bluish
+    assert!(colors[4..].iter().all(|c| c.0[2] >= c.0[0]));
+}
+
+#[test]
+fn test_draw_lines_texture() {
+    use crate::vector::Vec2;
+    let draw_lines = Texture::DrawLines(vec![DrawLine {
+        line: Line(Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0)),
+        color: RED,
+        end_color: None,
+        style: LineStyle::Solid,
+    }]);
+    let red_tiles = Texture::<Vec2>::make_tile_texture(&[FACE_SCALE], &vec![1, 1]).set_color(RED);
+    // merging keeps every line of both textures
+    let Texture::DrawLines(merged) = red_tiles.merged_with(&draw_lines) else {
+        panic!("merging with draw lines should give draw lines")
+    };
+    assert_eq!(merged.len(), 5);
+    // each end of a line is blended by its position
+    let Texture::DrawLines(graded) = draw_lines.with_gradient(0, WHITE) else {
+        panic!("gradient should keep draw lines")
+    };
+    assert_eq!(graded[0].color.0, RED.0);
+    assert_eq!(graded[0].end_color.map(|c| c.0), Some(WHITE.0));
+}
+
+#[test]
+fn test_tile_lod_crossfade() {
+    use crate::vector::Vec2;
+    let texture = Texture::<Vec2>::make_tile_texture(&[FACE_SCALE], &vec![4, 4]);
+    let detail = texture.detail().unwrap();
+    // 4x4, 2x2 and 1x1 tiles
+    assert_eq!(detail.len(), 4 * (16 + 4 + 1));
+    // each level's share of the tiles adds up to one at every level of detail
+    for i in 0..=20 {
+        let d = (i as Field) / 20.0;
+        let total: Field = [0, 4 * 16, 4 * 20]
+            .iter()
+            .map(|&first_line| detail[first_line].alpha(d))
+            .sum();
+        assert!(
+            (total - 1.0).abs() < 1e-5,
+            "detail {}: total alpha {}",
+            d,
+            total
+        );
+    }
+}
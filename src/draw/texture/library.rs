@@ -0,0 +1,271 @@
+use serde::{Deserialize, Serialize};
+use specs::{Component, VecStorage};
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use super::Texture;
+use crate::constants::FACE_SCALE;
+use crate::graphics::colors::{Color, DEFAULT_COLOR, GRAY, WHITE};
+use crate::vector::{Field, VecIndex, VectorTrait};
+
+// as a component, names the library texture an entity was built with, so it can be saved
+#[derive(Component, PartialEq, Eq, Hash, Clone, Serialize, Deserialize, Debug)]
+#[storage(VecStorage)]
+pub struct TextureLabel(pub String);
+impl Display for TextureLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TextureLabel({})", self.0)
+    }
+}
+impl TextureLabel {
+    pub fn from_str(str: &str) -> Self {
+        TextureLabel(str.to_string())
+    }
+}
+
+// the generator behind a texture. divisions are given per axis of the face, and are cut short
+// or padded with 1 to fit the dimension, so that one descriptor works in 3d and 4d
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum TextureKind {
+    Default,
+    Tiles {
+        divisions: Vec<i32>,
+        scales: Vec<Field>,
+    },
+    Fuzz,
+    Stripes {
+        axis: VecIndex,
+        n_stripes: usize,
+        lines_per_stripe: usize,
+    },
+    Checker {
+        divisions: Vec<i32>,
+        scale: Field,
+        // the color of the other cells
+        second_color: Color,
+    },
+    Shells {
+        n_shells: usize,
+    },
+    NoiseFuzz {
+        frequency: Field,
+        seed: u32,
+    },
+    // a sign, written with the stroke font
+    Text {
+        text: String,
+    },
+}
+
+// blends the texture's colors towards end_color along an axis of the face
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Gradient {
+    pub axis: VecIndex,
+    pub end_color: Color,
+}
+
+// describes a texture by its parameters, so that it can be saved and rebuilt in any dimension
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct TextureDescriptor {
+    pub kind: TextureKind,
+    pub color: Color,
+    // number of fuzz points. these are added over the texture, except for the fuzz kinds,
+    // where they are the whole texture
+    pub fuzz: usize,
+    pub gradient: Option<Gradient>,
+}
+impl TextureDescriptor {
+    pub fn new(kind: TextureKind) -> Self {
+        Self {
+            kind,
+            color: DEFAULT_COLOR,
+            fuzz: 0,
+            gradient: None,
+        }
+    }
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+    pub fn with_fuzz(mut self, fuzz: usize) -> Self {
+        self.fuzz = fuzz;
+        self
+    }
+    pub fn with_gradient(mut self, axis: VecIndex, end_color: Color) -> Self {
+        self.gradient = Some(Gradient { axis, end_color });
+        self
+    }
+    pub fn build<V: VectorTrait>(&self) -> Texture<V> {
+        let fit_divisions = |divisions: &[i32]| -> Vec<i32> {
+            (0..V::DIM as usize)
+                .map(|i| divisions.get(i).copied().unwrap_or(1))
+                .collect()
+        };
+        let fuzz = match &self.kind {
+            TextureKind::NoiseFuzz { frequency, seed } => {
+                Texture::make_noise_fuzz_texture(self.fuzz, *frequency, *seed)
+            }
+            _ => Texture::make_fuzz_texture(self.fuzz),
+        }
+        .set_color(self.color);
+        let pattern = match &self.kind {
+            TextureKind::Default => Some(Texture::DefaultLines { color: self.color }),
+            TextureKind::Tiles { divisions, scales } => Some(Texture::make_tile_texture(
+                scales,
+                &fit_divisions(divisions),
+            )),
+            TextureKind::Fuzz | TextureKind::NoiseFuzz { .. } => None,
+            TextureKind::Stripes {
+                axis,
+                n_stripes,
+                lines_per_stripe,
+            } => Some(Texture::make_stripe_texture(
+                *axis,
+                *n_stripes,
+                *lines_per_stripe,
+            )),
+            TextureKind::Checker { .. } => None,
+            TextureKind::Shells { n_shells } => Some(Texture::make_shell_texture(*n_shells)),
+            TextureKind::Text { text } => Some(Texture::make_text_texture(text)),
+        }
+        .map(|pattern| pattern.set_color(self.color));
+        // checker textures come with their colors
+        let pattern = match &self.kind {
+            TextureKind::Checker {
+                divisions,
+                scale,
+                second_color,
+            } => Some(Texture::make_checker_texture(
+                &fit_divisions(divisions),
+                *scale,
+                [self.color, *second_color],
+            )),
+            _ => pattern,
+        };
+        let texture = match (pattern, self.fuzz) {
+            (Some(pattern), 0) => pattern,
+            (Some(pattern), _) => pattern.merged_with(&fuzz),
+            (None, _) => fuzz,
+        };
+        match &self.gradient {
+            Some(gradient) => texture.with_gradient(gradient.axis, gradient.end_color),
+            None => texture,
+        }
+    }
+}
+
+// named textures, kept alongside RefShapes so that levels can refer to textures by name
+#[derive(Clone, Default, Serialize, Deserialize, Debug)]
+pub struct TextureLibrary(HashMap<TextureLabel, TextureDescriptor>);
+impl TextureLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn get(&self, key: &TextureLabel) -> Option<&TextureDescriptor> {
+        self.0.get(key)
+    }
+    pub fn get_unwrap(&self, key: &TextureLabel) -> &TextureDescriptor {
+        self.get(key)
+            .unwrap_or_else(|| panic!("Texture {} not found", key))
+    }
+    pub fn insert(
+        &mut self,
+        key: TextureLabel,
+        value: TextureDescriptor,
+    ) -> Option<TextureDescriptor> {
+        self.0.insert(key, value)
+    }
+    pub fn build<V: VectorTrait>(&self, key: &TextureLabel) -> Texture<V> {
+        self.get_unwrap(key).build()
+    }
+}
+
+// the textures shared by the levels
+pub fn build_texture_library(fuzz_num: usize) -> TextureLibrary {
+    let mut library = TextureLibrary::new();
+    for (label, descriptor) in [
+        (
+            "Tiles",
+            TextureDescriptor::new(TextureKind::Tiles {
+                divisions: vec![4, 4, 4],
+                scales: vec![FACE_SCALE],
+            }),
+        ),
+        (
+            "GradientTiles",
+            TextureDescriptor::new(TextureKind::Tiles {
+                divisions: vec![4, 4, 4],
+                scales: vec![FACE_SCALE],
+            })
+            .with_gradient(0, GRAY),
+        ),
+        (
+            "FuzzyTiles",
+            TextureDescriptor::new(TextureKind::Tiles {
+                divisions: vec![2, 2, 2],
+                scales: vec![FACE_SCALE],
+            })
+            .with_fuzz(fuzz_num),
+        ),
+        (
+            "Stripes",
+            TextureDescriptor::new(TextureKind::Stripes {
+                axis: 0,
+                n_stripes: 4,
+                lines_per_stripe: 3,
+            }),
+        ),
+        (
+            "Checker",
+            TextureDescriptor::new(TextureKind::Checker {
+                divisions: vec![3, 3, 3],
+                scale: FACE_SCALE,
+                second_color: WHITE,
+            }),
+        ),
+        (
+            "Shells",
+            TextureDescriptor::new(TextureKind::Shells { n_shells: 4 }),
+        ),
+        (
+            "Noise",
+            TextureDescriptor::new(TextureKind::NoiseFuzz {
+                frequency: 3.0,
+                seed: 0,
+            })
+            .with_fuzz(fuzz_num),
+        ),
+    ] {
+        library.insert(TextureLabel::from_str(label), descriptor);
+    }
+    library
+}
+
+#[test]
+fn test_texture_descriptor_round_trip() {
+    use crate::graphics::colors::GREEN;
+    use crate::vector::{Vec2, Vec3};
+
+    let library = build_texture_library(10);
+    let json = serde_json::to_string(&library).unwrap();
+    let loaded: TextureLibrary = serde_json::from_str(&json).unwrap();
+    let descriptor = loaded
+        .get_unwrap(&TextureLabel::from_str("Checker"))
+        .clone()
+        .with_color(GREEN);
+    // the same descriptor builds textures for faces of either dimension
+    fn line_colors<U>(texture: Texture<U>) -> Vec<Color> {
+        match texture {
+            Texture::Lines {
+                colors: Some(colors),
+                ..
+            } => colors,
+            _ => panic!("expected a checker texture"),
+        }
+    }
+    let colors_2d = line_colors(descriptor.build::<Vec2>());
+    let colors_3d = line_colors(descriptor.build::<Vec3>());
+    assert_eq!(colors_2d.len(), 9 * 4);
+    assert_eq!(colors_3d.len(), 27 * 12);
+    assert_eq!(colors_3d[0], GREEN);
+}
@@ -0,0 +1,240 @@
+use itertools::Itertools;
+
+use super::{fuzz_detail, pointlike_line, Texture};
+use crate::constants::PI;
+use crate::geometry::Line;
+use crate::graphics::colors::{Color, DEFAULT_COLOR};
+use crate::vector::{Field, VecIndex, VectorTrait};
+
+// procedural textures. like the tile and fuzz textures, these are drawn in the unit cube
+// of the face's texture space, and placed on the face by its TextureMapping
+
+// segments in each circle of a shell texture
+const SHELL_SEGMENTS: usize = 24;
+// noise fuzz gives up after this many rejected points per line
+const NOISE_FUZZ_TRIES: usize = 20;
+
+// edges of the axis aligned box between min and max. the box may be flat along some axes,
+// e.g. a slice of the unit cube is drawn as the outline of a (d - 1)-cube
+pub fn box_edges<V: VectorTrait>(min: V, max: V) -> Vec<Line<V>> {
+    let axes: Vec<VecIndex> = (0..V::DIM).filter(|&i| max[i] > min[i]).collect();
+    let corner = |bits: usize| {
+        let mut v = min;
+        for (k, &axis) in axes.iter().enumerate() {
+            if (bits >> k) & 1 == 1 {
+                v[axis] = max[axis];
+            }
+        }
+        v
+    };
+    let mut lines = Vec::new();
+    for bits in 0..(1usize << axes.len()) {
+        for k in (0..axes.len()).filter(|k| (bits >> k) & 1 == 0) {
+            lines.push(Line(corner(bits), corner(bits | (1 << k))));
+        }
+    }
+    lines
+}
+
+// a circle in the plane of axes i and j
+fn circle_lines<V: VectorTrait>(
+    center: V,
+    radius: Field,
+    i: VecIndex,
+    j: VecIndex,
+) -> Vec<Line<V>> {
+    let point = |s: usize| {
+        let angle = 2.0 * PI * (s as Field) / (SHELL_SEGMENTS as Field);
+        center + (V::one_hot(i) * angle.cos() + V::one_hot(j) * angle.sin()) * radius
+    };
+    (0..SHELL_SEGMENTS)
+        .map(|s| Line(point(s), point(s + 1)))
+        .collect()
+}
+
+fn hash_lattice_point(point: &[i32], seed: u32) -> u32 {
+    let mut h = seed.wrapping_mul(0x9E37_79B9);
+    for &c in point {
+        h ^= (c as u32).wrapping_mul(0x85EB_CA6B);
+        h = h.rotate_left(13).wrapping_mul(0xC2B2_AE35);
+    }
+    h ^ (h >> 16)
+}
+
+fn smoothstep(t: Field) -> Field {
+    t * t * (3.0 - 2.0 * t)
+}
+
+// smooth value noise in [0, 1], with features about 1 / frequency apart.
+// random values on an integer lattice are interpolated over each cell
+pub fn value_noise<V: VectorTrait>(pos: V, frequency: Field, seed: u32) -> Field {
+    let scaled = pos * frequency;
+    let cell: Vec<i32> = scaled.iter().map(|x| x.floor() as i32).collect();
+    let weights: Vec<Field> = scaled
+        .iter()
+        .zip(&cell)
+        .map(|(x, &c)| smoothstep(x - c as Field))
+        .collect();
+    (0..(1usize << V::DIM))
+        .map(|bits| {
+            let corner: Vec<i32> = cell
+                .iter()
+                .enumerate()
+                .map(|(axis, &c)| c + ((bits >> axis) & 1) as i32)
+                .collect();
+            let weight: Field = weights
+                .iter()
+                .enumerate()
+                .map(|(axis, &w)| match (bits >> axis) & 1 {
+                    1 => w,
+                    _ => 1.0 - w,
+                })
+                .product();
+            weight * hash_lattice_point(&corner, seed) as Field / u32::MAX as Field
+        })
+        .sum()
+}
+
+impl<V: VectorTrait> Texture<V> {
+    // n stripes across the given axis, each hatched with lines_per_stripe slices
+    pub fn make_stripe_texture(axis: VecIndex, n_stripes: usize, lines_per_stripe: usize) -> Self {
+        let stripe_width = 0.5 / (n_stripes as Field);
+        let lines = iproduct!(0..n_stripes, 0..lines_per_stripe)
+            .flat_map(|(stripe, line)| {
+                let x = (stripe as Field) / (n_stripes as Field)
+                    + stripe_width * (line as Field + 0.5) / (lines_per_stripe as Field);
+                box_edges(
+                    V::one_hot(axis) * x,
+                    V::ones() + V::one_hot(axis) * (x - 1.0),
+                )
+            })
+            .collect();
+        Texture::Lines {
+            lines,
+            color: DEFAULT_COLOR,
+            detail: None,
+            colors: None,
+        }
+    }
+    // outlines of the cells of a grid, alternating between two colors.
+    // cells are shrunk by scale about their centers so that neighbours don't share edges
+    pub fn make_checker_texture(n_divisions: &[i32], scale: Field, colors: [Color; 2]) -> Self {
+        if V::DIM != n_divisions.len() as VecIndex {
+            panic!(
+                "make_checker_texture: Expected n_divisions.len()={} but got {}",
+                V::DIM,
+                n_divisions.len()
+            );
+        }
+        let cell_size = V::from_iter(
+            n_divisions
+                .iter()
+                .map(|&n| 1.0 / (n as Field))
+                .collect_vec()
+                .iter(),
+        );
+        let (lines, line_colors) = n_divisions
+            .iter()
+            .map(|&n| 0..n)
+            .multi_cartesian_product()
+            .flat_map(|cell| {
+                let color = colors[(cell.iter().sum::<i32>() % 2) as usize];
+                let center = V::from_iter(
+                    cell.iter()
+                        .zip(n_divisions)
+                        .map(|(&i, &n)| (i as Field + 0.5) / (n as Field))
+                        .collect_vec()
+                        .iter(),
+                );
+                let half = cell_size * (scale / 2.0);
+                box_edges(center - half, center + half)
+                    .into_iter()
+                    .map(move |line| (line, color))
+            })
+            .unzip();
+        Texture::Lines {
+            lines,
+            color: colors[0],
+            detail: None,
+            colors: Some(line_colors),
+        }
+    }
+    // concentric spheres about the center of the face, each drawn as a circle in every
+    // pair of axes. the largest touches the edges of the face
+    pub fn make_shell_texture(n_shells: usize) -> Self {
+        let center = V::ones() * 0.5;
+        let lines = (1..=n_shells)
+            .flat_map(|k| {
+                let radius = 0.5 * (k as Field) / (n_shells as Field);
+                (0..V::DIM)
+                    .tuple_combinations()
+                    .flat_map(move |(i, j)| circle_lines(center, radius, i, j))
+            })
+            .collect();
+        Texture::Lines {
+            lines,
+            color: DEFAULT_COLOR,
+            detail: None,
+            colors: None,
+        }
+    }
+    // fuzz with patchy density, following value noise. like make_fuzz_texture,
+    // later points are dropped first at lower levels of detail
+    pub fn make_noise_fuzz_texture(n: usize, frequency: Field, seed: u32) -> Self {
+        let lines: Vec<Line<V>> = (0..n * NOISE_FUZZ_TRIES)
+            .map(|_| V::random())
+            .filter(|&pos| {
+                // stretch the noise so that patches are clearly thicker or thinner
+                let density = ((value_noise(pos, frequency, seed) - 0.25) * 2.0).clamp(0.0, 1.0);
+                rand::random::<Field>() < density
+            })
+            .take(n)
+            .map(pointlike_line)
+            .collect();
+        Texture::Lines {
+            detail: Some(fuzz_detail(lines.len())),
+            colors: None,
+            lines,
+            color: DEFAULT_COLOR,
+        }
+    }
+}
+
+#[test]
+fn test_procedural_textures() {
+    use crate::graphics::colors::{RED, WHITE};
+    use crate::vector::{Vec2, Vec3};
+
+    // a 3d slice through the unit cube is outlined by a square
+    assert_eq!(box_edges(Vec3::zero(), Vec3::ones()).len(), 12);
+    assert_eq!(
+        box_edges(
+            Vec3::one_hot(0) * 0.5,
+            Vec3::ones() - Vec3::one_hot(0) * 0.5
+        )
+        .len(),
+        4
+    );
+
+    let checker = Texture::<Vec2>::make_checker_texture(&[2, 2], 0.8, [RED, WHITE]);
+    let Texture::Lines {
+        lines,
+        colors: Some(colors),
+        ..
+    } = checker
+    else {
+        panic!("checker texture should have colored lines")
+    };
+    // cells are outlined in turn, and neighbours alternate colors
+    assert_eq!(lines.len(), 4 * 4);
+    let cell_colors: Vec<Color> = colors.into_iter().step_by(4).collect();
+    assert_eq!(cell_colors, vec![RED, WHITE, WHITE, RED]);
+
+    for i in 0..50 {
+        let pos = Vec3::new(i as Field * 0.37, 1.3, -(i as Field) * 0.11);
+        let noise = value_noise(pos, 2.0, 7);
+        assert!((0.0..=1.0).contains(&noise));
+        assert_eq!(noise, value_noise(pos, 2.0, 7));
+        assert!((noise - value_noise(pos + Vec3::one_hot(0) * 1e-3, 2.0, 7)).abs() < 0.05);
+    }
+}
@@ -0,0 +1,179 @@
+use super::Texture;
+use crate::geometry::Line;
+use crate::graphics::colors::DEFAULT_COLOR;
+use crate::vector::{Field, VectorTrait};
+
+// a stroke font in the style of the hershey fonts. each glyph is a list of polylines on a grid
+// GLYPH_WIDTH wide and GLYPH_HEIGHT tall, with y pointing up. polylines are separated by spaces,
+// and each point is written as two digits, x then y
+const GLYPH_WIDTH: Field = 4.0;
+const GLYPH_HEIGHT: Field = 6.0;
+const GLYPH_ADVANCE: Field = 6.0;
+const LINE_ADVANCE: Field = 9.0;
+
+fn glyph_strokes(c: char) -> &'static str {
+    match c.to_ascii_uppercase() {
+        'A' => "0004264440 0343",
+        'B' => "00063645443303 3342413000",
+        'C' => "4536160501103041",
+        'D' => "00063645413000",
+        'E' => "46060040 0333",
+        'F' => "460600 0333",
+        'G' => "45361605011030414323",
+        'H' => "0006 4046 0343",
+        'I' => "1636 2620 1030",
+        'J' => "4641301001",
+        'K' => "0006 4602 1340",
+        'L' => "060040",
+        'M' => "0006234640",
+        'N' => "00064046",
+        'O' => "100105163645413010",
+        'P' => "00063645443303",
+        'Q' => "100105163645413010 2240",
+        'R' => "00063645443303 2340",
+        'S' => "453616050413334241301001",
+        'T' => "0646 2620",
+        'U' => "060110304146",
+        'V' => "062046",
+        'W' => "0610233046",
+        'X' => "0046 0640",
+        'Y' => "0623 4623 2320",
+        'Z' => "06464000",
+        '0' => "100105163645413010 0145",
+        '1' => "152620 1030",
+        '2' => "05163645440040",
+        '3' => "05163645443313 334241301001",
+        '4' => "30360242",
+        '5' => "4606033342413000",
+        '6' => "4536160501103041423303",
+        '7' => "064610",
+        '8' => "13040516364544331302011030414233",
+        '9' => "0110304145361605041343",
+        ' ' => "",
+        '-' => "0343",
+        '+' => "0343 2125",
+        '_' => "0040",
+        '.' => "2021",
+        ',' => "2110",
+        ':' => "2122 2425",
+        '!' => "2622 2021",
+        '/' => "0046",
+        '#' => "1016 3036 0242 0444",
+        '(' => "36252130",
+        ')' => "16252110",
+        _ => "05163645442322 2021", // '?'
+    }
+}
+
+fn glyph_lines(c: char) -> impl Iterator<Item = Line<[Field; 2]>> {
+    glyph_strokes(c).split(' ').flat_map(|polyline| {
+        let points: Vec<[Field; 2]> = polyline
+            .as_bytes()
+            .chunks(2)
+            .map(|xy| [(xy[0] - b'0') as Field, (xy[1] - b'0') as Field])
+            .collect();
+        points
+            .windows(2)
+            .map(|pair| Line(pair[0], pair[1]))
+            .collect::<Vec<_>>()
+    })
+}
+
+// strokes of the text in glyph units, with the first line's baseline along y = 0.
+// returns the lines along with the width and height of the text
+fn layout_text(text: &str) -> (Vec<Line<[Field; 2]>>, Field, Field) {
+    let text_lines: Vec<&str> = text.lines().collect();
+    let mut lines = Vec::new();
+    for (row, text_line) in text_lines.iter().enumerate() {
+        for (col, c) in text_line.chars().enumerate() {
+            let offset = [col as Field * GLYPH_ADVANCE, -(row as Field) * LINE_ADVANCE];
+            lines.extend(
+                glyph_lines(c).map(|line| line.map(|p| [p[0] + offset[0], p[1] + offset[1]])),
+            );
+        }
+    }
+    let n_cols = text_lines
+        .iter()
+        .map(|l| l.chars().count())
+        .max()
+        .unwrap_or(0);
+    let width = match n_cols {
+        0 => 0.0,
+        n => (n - 1) as Field * GLYPH_ADVANCE + GLYPH_WIDTH,
+    };
+    let height = match text_lines.len() {
+        0 => 0.0,
+        n => (n - 1) as Field * LINE_ADVANCE + GLYPH_HEIGHT,
+    };
+    (lines, width, height)
+}
+
+// text written in the plane spanned by right and up, with glyphs of the given height.
+// origin is the bottom left of the last line of text
+pub fn text_lines<V: VectorTrait>(
+    text: &str,
+    origin: V,
+    right: V,
+    up: V,
+    height: Field,
+) -> Vec<Line<V>> {
+    let (lines, _, text_height) = layout_text(text);
+    let scale = height / GLYPH_HEIGHT;
+    let bottom = text_height - GLYPH_HEIGHT;
+    lines
+        .into_iter()
+        .map(|line| line.map(|p| origin + right * (p[0] * scale) + up * ((p[1] + bottom) * scale)))
+        .collect()
+}
+
+// width of the text written with text_lines
+pub fn text_width(text: &str, height: Field) -> Field {
+    layout_text(text).1 * height / GLYPH_HEIGHT
+}
+
+impl<V: VectorTrait> Texture<V> {
+    // text centered on the face, as large as fits. it runs along the first axis of the face
+    // with the second axis up, halfway through any remaining axes
+    pub fn make_text_texture(text: &str) -> Self {
+        const MARGIN: Field = 0.1;
+        let (_, width, height) = layout_text(text);
+        let height_scale = match V::DIM {
+            1 => Field::INFINITY,
+            _ => (1.0 - 2.0 * MARGIN) / height.max(1.0),
+        };
+        let scale = ((1.0 - 2.0 * MARGIN) / width.max(1.0)).min(height_scale);
+        let up = match V::DIM {
+            1 => V::zero(),
+            _ => V::one_hot(1),
+        };
+        let center = V::ones() * 0.5;
+        let origin = center - V::one_hot(0) * (width * scale / 2.0) - up * (height * scale / 2.0);
+        Texture::Lines {
+            lines: text_lines(text, origin, V::one_hot(0), up, GLYPH_HEIGHT * scale),
+            color: DEFAULT_COLOR,
+            detail: None,
+            colors: None,
+        }
+    }
+}
+
+#[test]
+fn test_text_layout() {
+    use crate::vector::Vec2;
+    // one stroke for '-', and a glyph of space between characters
+    let (lines, width, height) = layout_text("-\n--");
+    assert_eq!(lines.len(), 3);
+    assert_eq!(
+        (width, height),
+        (GLYPH_ADVANCE + GLYPH_WIDTH, LINE_ADVANCE + GLYPH_HEIGHT)
+    );
+    let Texture::Lines { lines, .. } = Texture::<Vec2>::make_text_texture("HI 42") else {
+        panic!("expected a line texture")
+    };
+    assert!(!lines.is_empty());
+    // the text fits on the face
+    assert!(lines
+        .iter()
+        .flat_map(|line| [line.0, line.1])
+        .all(|p| p.iter().all(|&x| (0.0..=1.0).contains(&x))));
+}
@@ -0,0 +1,255 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use super::texture::procedural::box_edges;
+use super::{DrawLine, LineStyle, ViewportShape};
+use crate::config::ViewConfig;
+use crate::constants::{PI, VIEWPORT_COLOR};
+use crate::geometry::{Line, Plane};
+use crate::vector::{Field, VecIndex, VectorTrait};
+
+use super::clipping::clip_line_planes;
+
+// segments in each curve of the viewport outline
+const OUTLINE_SEGMENTS: usize = 48;
+// length of the outline of a plane viewport that is not closed by the other planes
+const OUTLINE_EXTENT: Field = 10.0;
+
+// one face of a plane viewport, keeping points x with normal . x <= threshold.
+// the normal is cut short or padded with 0 to fit the dimension, so one config works in 3d and 4d
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ViewportPlane {
+    pub normal: Vec<Field>,
+    pub threshold: Field,
+}
+
+// planes of a plane viewport in the projected space, with unit normals facing out.
+// planes whose normals lie entirely in the missing axes are dropped
+pub fn viewport_planes<U: VectorTrait>(planes: &[ViewportPlane]) -> Vec<Plane<U>> {
+    planes
+        .iter()
+        .filter_map(|plane| {
+            let normal = U::from_iter(
+                (0..U::DIM as usize)
+                    .map(|i| plane.normal.get(i).copied().unwrap_or(0.0))
+                    .collect_vec()
+                    .iter(),
+            );
+            let norm = normal.norm();
+            (norm > 0.0).then(|| Plane {
+                normal: normal / norm,
+                threshold: plane.threshold / norm,
+            })
+        })
+        .collect()
+}
+
+// half widths of a superellipsoid along each axis. missing radii are the view radius
+pub fn superellipsoid_radii<U: VectorTrait>(radii: &[Field], view_radius: Field) -> U {
+    U::from_iter(
+        (0..U::DIM as usize)
+            .map(|i| radii.get(i).copied().unwrap_or(view_radius))
+            .collect_vec()
+            .iter(),
+    )
+}
+
+impl ViewportShape {
+    // names of the shapes, in the order of ViewportShape::preset
+    pub const NAMES: [&'static str; 7] = [
+        "Cube",
+        "Sphere",
+        "Cylinder",
+        "Tube",
+        "Planes",
+        "Superellipsoid",
+        "None",
+    ];
+    pub fn index(&self) -> usize {
+        match self {
+            ViewportShape::Cube => 0,
+            ViewportShape::Sphere => 1,
+            ViewportShape::Cylinder => 2,
+            ViewportShape::Tube => 3,
+            ViewportShape::Planes(_) => 4,
+            ViewportShape::Superellipsoid { .. } => 5,
+            ViewportShape::None => 6,
+        }
+    }
+    // the shape at index of NAMES. shapes with parameters are given a default,
+    // a diamond for the plane viewport and a rounded cube for the superellipsoid
+    pub fn preset(index: usize, radius: Field) -> Self {
+        match index {
+            0 => ViewportShape::Cube,
+            1 => ViewportShape::Sphere,
+            2 => ViewportShape::Cylinder,
+            3 => ViewportShape::Tube,
+            4 => ViewportShape::Planes(
+                iproduct!([-1.0, 1.0], [-1.0, 1.0])
+                    .map(|(x, y)| vec![x, y, 0.0])
+                    .chain([vec![0.0, 0.0, -1.0], vec![0.0, 0.0, 1.0]])
+                    .map(|normal| ViewportPlane {
+                        normal,
+                        threshold: radius,
+                    })
+                    .collect(),
+            ),
+            5 => ViewportShape::Superellipsoid {
+                radii: vec![radius],
+                exponent: 4.0,
+            },
+            _ => ViewportShape::None,
+        }
+    }
+}
+
+// a closed curve in the plane of axes i and j, with |x_i / r_i|^exponent + |x_j / r_j|^exponent = 1.
+// an exponent of 2 gives an ellipse
+fn superellipse_lines<U: VectorTrait>(
+    center: U,
+    radii: U,
+    exponent: Field,
+    i: VecIndex,
+    j: VecIndex,
+) -> Vec<Line<U>> {
+    let signed_pow = |x: Field| x.signum() * x.abs().powf(2.0 / exponent);
+    let point = |s: usize| {
+        let angle = 2.0 * PI * (s as Field) / (OUTLINE_SEGMENTS as Field);
+        center
+            + U::one_hot(i) * (radii[i] * signed_pow(angle.cos()))
+            + U::one_hot(j) * (radii[j] * signed_pow(angle.sin()))
+    };
+    (0..OUTLINE_SEGMENTS)
+        .map(|s| Line(point(s), point(s + 1)))
+        .collect()
+}
+
+// the edges of the volume inside the planes. in 2d these lie along each plane,
+// and in 3d along the intersections of pairs of planes. each is clipped by the other planes
+fn planes_outline<U: VectorTrait>(planes: &[Plane<U>]) -> Vec<Line<U>> {
+    let clip = |line: Line<U>, skip: &[usize]| {
+        let others = planes
+            .iter()
+            .enumerate()
+            .filter(|(k, _)| !skip.contains(k))
+            .map(|(_, plane)| plane.clone())
+            .collect_vec();
+        clip_line_planes(line, &others)
+    };
+    let edge = |point: U, direction: U| {
+        Line(
+            point - direction * OUTLINE_EXTENT,
+            point + direction * OUTLINE_EXTENT,
+        )
+    };
+    match U::DIM {
+        2 => planes
+            .iter()
+            .enumerate()
+            .filter_map(|(k, plane)| {
+                let direction = U::one_hot(0) * -plane.normal[1] + U::one_hot(1) * plane.normal[0];
+                clip(edge(plane.normal * plane.threshold, direction), &[k])
+            })
+            .collect(),
+        3 => (0..planes.len())
+            .tuple_combinations()
+            .filter_map(|(k, l)| {
+                let (n, m) = (planes[k].normal, planes[l].normal);
+                let direction = U::from_iter(
+                    [
+                        n[1] * m[2] - n[2] * m[1],
+                        n[2] * m[0] - n[0] * m[2],
+                        n[0] * m[1] - n[1] * m[0],
+                    ]
+                    .iter(),
+                );
+                let sin2 = direction.dot(direction);
+                if sin2 < 1e-6 {
+                    return None;
+                }
+                // the point on both planes nearest the origin
+                let cos = n.dot(m);
+                let (a, b) = (planes[k].threshold, planes[l].threshold);
+                let point = (n * (a - b * cos) + m * (b - a * cos)) / sin2;
+                clip(edge(point, direction / sin2.sqrt()), &[k, l])
+            })
+            .collect(),
+        _ => panic!("Invalid dimension for viewport outline"),
+    }
+}
+
+// the boundary of the viewport volume in the projected space
+pub fn viewport_outline<U: VectorTrait>(view_config: &ViewConfig) -> Vec<DrawLine<U>> {
+    let (r, h) = (view_config.radius, view_config.height);
+    let long_axis = 1;
+    let radial_axes = (0..U::DIM).filter(|&i| i != long_axis).collect_vec();
+    // the circles, or points in 2d, about the long axis at each height, joined along the long axis
+    let tube_lines = |heights: &[Field]| {
+        let rings = heights.iter().flat_map(|&y| {
+            radial_axes
+                .iter()
+                .tuple_combinations()
+                .flat_map(move |(&i, &j)| {
+                    superellipse_lines(U::one_hot(long_axis) * y, U::ones() * r, 2.0, i, j)
+                })
+        });
+        let walls = iproduct!(radial_axes.iter(), [-r, r]).map(|(&i, x)| {
+            Line(
+                U::one_hot(i) * x - U::one_hot(long_axis) * h,
+                U::one_hot(i) * x + U::one_hot(long_axis) * h,
+            )
+        });
+        rings.chain(walls).collect_vec()
+    };
+    let superellipsoid_lines = |radii: U, exponent: Field| {
+        (0..U::DIM)
+            .tuple_combinations()
+            .flat_map(|(i, j)| superellipse_lines(U::zero(), radii, exponent, i, j))
+            .collect_vec()
+    };
+    let lines = match &view_config.viewport_shape {
+        ViewportShape::Cube => box_edges(-U::ones() * r, U::ones() * r),
+        ViewportShape::Sphere => superellipsoid_lines(U::ones() * r, 2.0),
+        ViewportShape::Cylinder => tube_lines(&[-h, h]),
+        // the tube is open along the long axis, so it is only drawn to the cylinder's height
+        ViewportShape::Tube => tube_lines(&[0.0]),
+        ViewportShape::Planes(planes) => planes_outline(&viewport_planes(planes)),
+        ViewportShape::Superellipsoid { radii, exponent } => {
+            superellipsoid_lines(superellipsoid_radii(radii, r), *exponent)
+        }
+        ViewportShape::None => Vec::new(),
+    };
+    lines
+        .into_iter()
+        .map(|line| DrawLine {
+            line,
+            color: VIEWPORT_COLOR,
+            end_color: None,
+            style: LineStyle::Solid,
+        })
+        .collect()
+}
+
+#[test]
+fn test_viewport_outline() {
+    use crate::vector::{Vec2, Vec3};
+    // the diamond preset is closed by its planes
+    let view_config = ViewConfig {
+        viewport_shape: ViewportShape::preset(4, 1.0),
+        ..Default::default()
+    };
+    let square = viewport_outline::<Vec2>(&view_config);
+    assert_eq!(square.len(), 4);
+    for draw_line in &square {
+        let length = (draw_line.line.1 - draw_line.line.0).norm();
+        assert!((length - (2.0 as Field).sqrt()).abs() < 1e-4);
+        assert!((draw_line.line.0.norm() - 1.0).abs() < 1e-4);
+    }
+    // in 3d, the diamond is extruded between the last two planes
+    assert_eq!(viewport_outline::<Vec3>(&view_config).len(), 12);
+
+    for (index, name) in ViewportShape::NAMES.iter().enumerate() {
+        let shape = ViewportShape::preset(index, 1.0);
+        assert_eq!(shape.index(), index, "{}", name);
+    }
+}
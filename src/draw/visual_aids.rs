@@ -4,9 +4,7 @@ use itertools::Itertools;
 
 use crate::{
     components::{Shape, Transform, Transformable},
-    constants::{
-        AXES_COLORS, CARDINAL_COLORS, HALF, HALF_PI, SKY_DISTANCE, SKY_FUZZ_SIZE, STAR_SIZE, ZERO,
-    },
+    constants::{AXES_COLORS, CARDINAL_COLORS, HALF, HALF_PI, SKY_DISTANCE, STAR_SIZE, ZERO},
     geometry::{
         shape::{
             buildshapes::{build_prism_2d, convex_shape_to_face_shape, ShapeBuilder},
@@ -18,7 +16,7 @@ use crate::{
     vector::{linspace, Field, VecIndex, VectorTrait},
 };
 
-use super::{texture::pointlike_line, DrawLine};
+use super::{texture::pointlike_line, DrawLine, LineStyle};
 
 pub fn calc_grid_lines<V: VectorTrait>(center: V, cell_size: Field, n: usize) -> Vec<Line<V>> {
     let axes = (0, 2, 3);
@@ -100,6 +98,8 @@ pub fn draw_axes<'a, V: VectorTrait + 'a>(
         .map(move |(i, color)| DrawLine {
             line: Line(center - V::one_hot(i) * len, center + V::one_hot(i) * len),
             color,
+            end_color: None,
+            style: LineStyle::Solid,
         })
 }
 
@@ -107,9 +107,12 @@ pub fn draw_stars<V: VectorTrait>() -> Vec<DrawLine<V>> {
     iproduct!((0..V::DIM), [false, true])
         .zip(CARDINAL_COLORS)
         .flat_map(|((axis, sign), color)| {
-            draw_star(axis, sign)
-                .into_iter()
-                .map(move |line| DrawLine { line, color })
+            draw_star(axis, sign).into_iter().map(move |line| DrawLine {
+                line,
+                color,
+                end_color: None,
+                style: LineStyle::Solid,
+            })
         })
         .collect_vec()
 }
@@ -126,54 +129,10 @@ fn draw_star<V: VectorTrait>(axis: VecIndex, sign: bool) -> Vec<Line<V>> {
     calc_wireframe_lines(&cube)
 }
 
-pub fn draw_horizon<V: VectorTrait>(n_lines: usize) -> Vec<Line<V>> {
-    match V::DIM {
-        3 => calc_wireframe_lines(
-            &(ShapeBuilder::build_prism(2, &[SKY_DISTANCE], &[12]))
-                .with_rotation(-1, 1, HALF_PI)
-                .build(),
-        ),
-        4 => (0..n_lines)
-            .map(|_| {
-                pointlike_sky_line({
-                    let u = random_sphere_point::<V::SubV>() * SKY_DISTANCE;
-                    V::from_iter(vec![u[0], ZERO, u[1], u[2]].iter())
-                })
-            })
-            .collect(),
-        _ => panic!("draw_horizon not supported in {} dim", V::DIM),
-    }
-}
-
-pub fn draw_sky<V: VectorTrait>(n_lines: usize) -> Vec<DrawLine<V>> {
-    (0..n_lines)
-        .map(|_| {
-            let pos = random_hemisphere_point(V::one_hot(1)) * SKY_DISTANCE;
-            DrawLine {
-                line: pointlike_sky_line(pos),
-                color: blend(CYAN, BLUE, pos.normalize().dot(V::one_hot(1))),
-            }
-        })
-        .collect_vec()
-}
-
 pub fn random_sphere_point<V: VectorTrait>() -> V {
     (V::random() - V::ones() * HALF).normalize()
 }
 
-fn random_hemisphere_point<V: VectorTrait>(normal: V) -> V {
-    let v: V = random_sphere_point();
-    if v.dot(normal) > ZERO {
-        v
-    } else {
-        -v
-    }
-}
-
 fn random_ball_point<V: VectorTrait>() -> V {
     random_sphere_point::<V>() * rand::random::<Field>().sqrt()
 }
-
-fn pointlike_sky_line<V: VectorTrait>(pos: V) -> Line<V> {
-    Line(pos, pos + random_sphere_point::<V>() * SKY_FUZZ_SIZE)
-}
@@ -3,11 +3,12 @@ pub use dispatcher::get_engine_dispatcher_builder;
 use specs::saveload; // TODO: revert to private
 
 use crate::collide;
-use crate::config::load_config;
+use crate::config::{load_config, Config};
 use crate::constants::FRAME_MS;
 use crate::ecs_utils::Componentable;
 use crate::graphics::DefaultGraphics;
 use crate::graphics::GraphicsTrait;
+use crate::graphics::InsetLines;
 use crate::input::ShapeManipulationState;
 use crate::saveload::Save;
 use crate::saveload::SaveMarker;
@@ -20,6 +21,9 @@ use std::marker::PhantomData;
 use std::time::{Duration, Instant};
 
 use crate::draw;
+use crate::draw::fill::DrawTriangleList;
+use crate::draw::split_view::SplitView;
+use crate::draw::ViewportShape;
 use crate::gui::UIArgs;
 use glium::glutin::{
     event::{Event, WindowEvent},
@@ -58,6 +62,7 @@ where
 
         // TODO: rm these when they are registered as part of a save/load system
         world.register::<SaveMarker>();
+        world.register::<TextureLabel>();
         world.insert::<SaveMarkerAllocator>(SaveMarkerAllocator::default());
 
         let mut dispatcher = get_engine_dispatcher_builder::<V>().build();
@@ -165,11 +170,23 @@ where
         //TODO: slow to build this
         //let ui_args = UIArgs::new_debug::<V>(&self.world, fps_timer.get_frame_length());
 
+        // apply the viewport shape picked in the ui
+        if let Some(index) = self.gui.as_mut().and_then(|gui| gui.take_viewport_choice()) {
+            let mut config = self.world.write_resource::<Config>();
+            config.view.viewport_shape = ViewportShape::preset(index, config.view.radius);
+        }
+
         //let ui_args = UIArgs::None;
         let ui_args = UIArgs::Simple {
             frame_duration: fps_timer.get_frame_length(),
             coins_collected: self.world.read_resource::<crate::coin::CoinsCollected>().0,
             coins_left: self.world.read_storage::<crate::coin::Coin>().count() as u32,
+            viewport_shape: self
+                .world
+                .read_resource::<Config>()
+                .view
+                .viewport_shape
+                .index(),
         };
         //gui update (all events)
         if let Some(ref mut gui) = &mut self.gui {
@@ -220,10 +237,26 @@ where
         // and gui args to graphics for drawing
         let draw_lines_data: ReadExpect<draw::DrawLineList<V::SubV>> = self.world.system_data();
         let draw_lines = &draw_lines_data.0;
-        self.graphics.update_buffer(draw_lines, display);
+        let config: ReadExpect<Config> = self.world.system_data();
+        let split_view: Read<SplitView<V>> = self.world.system_data();
+        let insets: Vec<InsetLines<V::SubV>> = split_view
+            .views
+            .iter()
+            .map(|view| InsetLines {
+                lines: &view.lines.0,
+                rect: view.rect,
+            })
+            .collect();
+        let lines_len =
+            draw_lines.len() + insets.iter().map(|inset| inset.lines.len()).sum::<usize>();
+        self.graphics.update_buffer(lines_len, display);
+        let triangles: Read<DrawTriangleList<V::SubV>> = self.world.system_data();
+        self.graphics.update_fill_buffer(&triangles.0, display);
 
         let mut target = display.draw();
-        target = self.graphics.draw_lines(draw_lines, target);
+        target = self
+            .graphics
+            .draw_lines(draw_lines, &config.view, &insets, target);
         //draw gui
         if let Some(ref mut gui) = &mut self.gui {
             gui.draw(display, &mut target);
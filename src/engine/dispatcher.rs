@@ -27,31 +27,71 @@ where
 {
     let ph = PhantomData::<V>;
     builder
-        .with(VisibilitySystem(ph), VisibilitySystem::NAME, &[])
+        .with(CullingSystem(ph), CullingSystem::NAME, &[])
+        .with(
+            VisibilitySystem(ph),
+            VisibilitySystem::NAME,
+            &[CullingSystem::NAME],
+        )
+        .with(
+            LevelOfDetailSystem(ph),
+            LevelOfDetailSystem::NAME,
+            &[CullingSystem::NAME],
+        )
+        //shade faces by the scene light
+        .with(
+            LightingSystem(ph),
+            LightingSystem::NAME,
+            &[CullingSystem::NAME],
+        )
         //determine what shapes are in front of other shapes
         .with(
             InFrontSystem(ph),
             InFrontSystem::NAME,
             &[VisibilitySystem::NAME],
         )
+        //fill visible faces
+        .with(
+            CalcFillsSystem(ph),
+            CalcFillsSystem::NAME,
+            &[VisibilitySystem::NAME, LightingSystem::NAME],
+        )
         //calculate and clip lines for each shape
         .with(
             CalcShapesLinesSystem(ph),
             CalcShapesLinesSystem::NAME,
-            &[InFrontSystem::NAME],
-        )
+            &[
+                InFrontSystem::NAME,
+                LevelOfDetailSystem::NAME,
+                LightingSystem::NAME,
+            ],
+        )
+        //mark the ground below the player and coins
+        .with(DropShadowSystem(ph), DropShadowSystem::NAME, &[])
         //draw selection box in the space
         .with(
             DrawLineCollectionSystem(ph),
             DrawLineCollectionSystem::NAME,
-            &[InFrontSystem::NAME],
+            &[InFrontSystem::NAME, DropShadowSystem::NAME],
+        )
+        //draw the sky around the player
+        .with(
+            SkySystem(ph),
+            SkySystem::NAME,
+            &[CalcShapesLinesSystem::NAME],
         )
         //project lines
         .with(
             TransformDrawLinesSystem(ph),
             TransformDrawLinesSystem::NAME,
-            &[CalcShapesLinesSystem::NAME, DrawLineCollectionSystem::NAME],
-        )
+            &[
+                CalcShapesLinesSystem::NAME,
+                DrawLineCollectionSystem::NAME,
+                SkySystem::NAME,
+            ],
+        )
+        // draw the level in the extra axis views
+        .with(SplitViewSystem(ph), SplitViewSystem::NAME, &[])
         // draw the cursor on the d - 1 screen
         .with(
             DrawCursorSystem(ph),
@@ -72,23 +112,30 @@ where
         .with(
             UpdateCameraSystem(ph),
             "update_camera",
-            &[CalcShapesLinesSystem::NAME],
+            &[CalcShapesLinesSystem::NAME, CalcFillsSystem::NAME],
         )
+        .with(ToggleFlySystem, ToggleFlySystem::NAME, &["update_camera"])
         .with(
             PlayerGravitySystem(ph),
             "player_gravity",
-            &["update_camera"],
+            &["update_camera", ToggleFlySystem::NAME],
         )
         .with(
             PlayerCollisionDetectionSystem(ph),
             "player_collision_detect",
             &["update_camera", "player_gravity"],
         )
+        .with(
+            PlayerPushSystem(ph),
+            "push_bodies",
+            &["player_collision_detect"],
+        )
         .with(
             PlayerStaticCollisionSystem(ph),
             "player_static_collision",
-            &["player_collision_detect"],
+            &["player_collision_detect", "push_bodies"],
         )
+        .with(RigidBodySystem(ph), "rigid_bodies", &["push_bodies"])
         .with(
             PlayerCoinCollisionSystem(ph),
             "player_coin_collision",
@@ -99,6 +146,7 @@ where
             "move_player",
             &["player_static_collision", "player_coin_collision"],
         )
+        .with(AnimationSystem(ph), "animation", &["move_player"])
         .with(
             ShapeTargetingSystem(ph),
             "shape_targeting",
@@ -114,6 +162,11 @@ where
             "manipulate_selected",
             &["select_target"],
         )
+        .with(
+            ToggleHiddenAxisColoringSystem,
+            ToggleHiddenAxisColoringSystem::NAME,
+            &["update_camera"],
+        )
         .with(CreateShapeSystem(ph), "create_shape", &[])
         .with(DuplicateShapeSystem(ph), "duplicate_shape", &[])
         .with(DeleteShapeSystem(ph), "delete_shape", &[])
@@ -121,14 +174,23 @@ where
         .with(
             TransformShapeSystem(ModSystem::typed_default(ph)),
             "transform_shapes",
-            &["manipulate_selected", "coin_spinning"],
+            &[
+                "manipulate_selected",
+                "coin_spinning",
+                "animation",
+                "rigid_bodies",
+            ],
         )
         .with(
             UpdateSelectionBox(ModSystem::typed_default(ph)),
             "update_selection_box",
             &["transform_shapes"],
         )
-        .with(UpdatePlayerBBox(ph), "update_player_bbox", &["move_player"]) //merge with above
+        .with(
+            UpdatePlayerBBox(ph),
+            "update_player_bbox",
+            &["move_player", "animation"],
+        ) //merge with above
         .with(
             UpdateBBoxSystem(ModSystem::typed_default(ph)),
             "update_all_bbox",
@@ -149,10 +211,24 @@ where
             "update_static_clipping",
             &["transform_shapes"],
         )
+        .with(
+            UpdateLinesCacheSystem(ModSystem::typed_default(ph), ModSystem::typed_default(ph)),
+            "update_lines_cache",
+            &["transform_shapes"],
+        )
+        .with(
+            UpdateBspTreeSystem(ModSystem::typed_default(ph)),
+            UpdateBspTreeSystem::NAME,
+            &["update_all_bball"],
+        )
         .with(
             ShapeCleanupSystem(ph),
             "shape_cleanup",
-            &["delete_shape", BBoxHashingSystem::NAME],
+            &[
+                "delete_shape",
+                BBoxHashingSystem::NAME,
+                UpdateBspTreeSystem::NAME,
+            ],
         )
     //.with(PrintDebugSystem(ph), "print_debug", &["update_camera"])
 }
@@ -124,6 +124,18 @@ impl<V: VectorTrait> SingleFace<V> {
         }
         out_points
     }
+    //the face as a flat convex region: the face plane, the face plane facing the other way,
+    //then the subface planes around its edges
+    pub fn slab_planes(&self, face_plane: &Plane<V>) -> Vec<Plane<V>> {
+        let back = Plane {
+            normal: -face_plane.normal,
+            threshold: -face_plane.threshold,
+        };
+        [face_plane.clone(), back]
+            .into_iter()
+            .chain(self.subfaces.0.iter().map(|sf| sf.plane.clone()))
+            .collect()
+    }
     //returns distance to nearest subface plane
     pub fn subface_normal_distance(&self, pos: V) -> (V, Field) {
         let (closest_subshape_plane, distance) =
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use glium::buffer::WriteMapping;
 use glium::vertex::Vertex;
 use glium::Display;
@@ -6,7 +8,11 @@ use glium::Surface;
 use glium::VertexBuffer;
 use specs::Write;
 
+use crate::config::{LineConfig, ViewConfig};
 use crate::constants::BACKGROUND_COLOR;
+use crate::draw::fill::DrawTriangle;
+use crate::draw::split_view::ScreenRect;
+use crate::draw::Projection;
 use crate::draw::{DrawLine, DrawVertex};
 use crate::geometry::shape::VertIndex;
 use crate::vector::VecIndex;
@@ -14,39 +20,64 @@ use crate::vector::VectorTrait;
 
 use self::matrices::build_perspective_matrix;
 use self::matrices::build_view_matrix;
+use self::matrices::Matrix4;
 use self::matrices::IDENTITY_MATRIX;
 use self::proj_line_vertex::ProjLineVertex;
 use self::simple_vertex::SimpleVertex;
+use self::stereo::eye_views;
 
 pub mod colors;
 mod matrices;
 mod proj_line_vertex;
 mod simple_vertex;
-
-const FRAGMENT_SHADER_SRC: &str = include_str!("graphics/simple-shader.frag");
+pub mod software;
+mod stereo;
 
 pub trait VertexTrait: Vertex {
     const NO_DRAW: Self;
     const LINE_BUFFER_SIZE: usize;
     const PRIMITIVE_TYPE: glium::index::PrimitiveType;
     const VERTEX_SHADER_SRC: &'static str;
+    const FRAGMENT_SHADER_SRC: &'static str;
     type Iter: Iterator<Item = Self>;
     fn vert_to_gl<V: VectorTrait>(vert: &DrawVertex<V>) -> Self;
     fn line_to_gl<V: VectorTrait>(draw_line: &DrawLine<V>) -> Vec<Self>;
     fn line_to_gl_iter<V: VectorTrait>(draw_line: &DrawLine<V>) -> Self::Iter;
-    fn line_thickness(dim: VecIndex) -> f32;
+    fn line_thickness(dim: VecIndex, line_config: &LineConfig) -> f32;
     // this may be slightly(?) faster than line_to_gl_iter, but i don't know how to make the arr size generic
     //fn line_to_gl_arr<V: VectorTrait>(maybe_line: &DrawLine<V>) -> [Self; 6];
 }
 
+// lines drawn over a region of the main view
+pub struct InsetLines<'a, V> {
+    pub lines: &'a [DrawLine<V>],
+    pub rect: ScreenRect,
+}
+
 pub trait GraphicsTrait {
     fn init(display: &Display) -> Self;
     fn draw_lines<V: VectorTrait>(
         &mut self,
         draw_lines: &[DrawLine<V>],
+        view_config: &ViewConfig,
+        insets: &[InsetLines<V>],
         target: glium::Frame,
     ) -> glium::Frame;
-    fn update_buffer<V: VectorTrait>(&mut self, draw_lines: &[DrawLine<V>], display: &Display);
+    fn update_buffer(&mut self, lines_len: usize, display: &Display);
+    fn update_fill_buffer<V: VectorTrait>(
+        &mut self,
+        triangles: &[DrawTriangle<V>],
+        display: &Display,
+    );
+}
+
+pub fn viewport_rect(rect: &ScreenRect, target_width: u32, target_height: u32) -> glium::Rect {
+    glium::Rect {
+        left: (rect.left * target_width as f32) as u32,
+        bottom: (rect.bottom * target_height as f32) as u32,
+        width: (rect.width * target_width as f32) as u32,
+        height: (rect.height * target_height as f32) as u32,
+    }
 }
 
 pub type DefaultGraphics = Graphics<ProjLineVertex>;
@@ -55,6 +86,11 @@ pub struct Graphics<X: Copy> {
     pub index_buffer: glium::IndexBuffer<u16>, //can we change this to VertIndex=usize?
     pub program: glium::Program,
     cur_lines_len: usize, // we store buffer size here because apparently calling vertex_buffer.len() is expensive
+    fill_buffer: glium::VertexBuffer<SimpleVertex>,
+    // vertices in the fill buffer, and how many of them are used this frame
+    fill_capacity: usize,
+    fill_len: usize,
+    fill_program: glium::Program,
 }
 impl<X: VertexTrait> Graphics<X> {
     pub fn new(display: &glium::Display) -> Self {
@@ -69,12 +105,113 @@ impl<X: VertexTrait> Graphics<X> {
             program: glium::Program::from_source(
                 display,
                 X::VERTEX_SHADER_SRC,
-                FRAGMENT_SHADER_SRC,
+                X::FRAGMENT_SHADER_SRC,
                 None,
             )
             .unwrap(),
             cur_lines_len: 0,
+            fill_buffer: glium::VertexBuffer::dynamic(display, &Vec::new()).unwrap(),
+            fill_capacity: 0,
+            fill_len: 0,
+            fill_program: glium::Program::from_source(
+                display,
+                simple_vertex::VERTEX_SHADER_SRC,
+                simple_vertex::FRAGMENT_SHADER_SRC,
+                None,
+            )
+            .unwrap(),
+        }
+    }
+}
+
+// filled faces add their color to what is behind them, so they don't need to be sorted
+const FILL_BLENDING: glium::Blend = glium::Blend {
+    color: glium::BlendingFunction::Addition {
+        source: glium::LinearBlendingFactor::SourceAlpha,
+        destination: glium::LinearBlendingFactor::One,
+    },
+    alpha: glium::BlendingFunction::Addition {
+        source: glium::LinearBlendingFactor::SourceAlpha,
+        destination: glium::LinearBlendingFactor::One,
+    },
+    constant_value: (0.0, 0.0, 0.0, 0.0),
+};
+
+impl<X: VertexTrait> Graphics<X> {
+    fn draw_fills<V: VectorTrait>(
+        &self,
+        target: &mut glium::Frame,
+        viewport: glium::Rect,
+        view: Matrix4,
+        projection: Projection,
+        color_mask: (bool, bool, bool, bool),
+    ) {
+        if self.fill_len == 0 {
+            return;
         }
+        let (width, height) = (viewport.width, viewport.height);
+        let draw_params = glium::DrawParameters {
+            blend: FILL_BLENDING,
+            color_mask,
+            viewport: Some(viewport),
+            ..Default::default()
+        };
+        let uniforms = uniform! {
+            perspective : build_perspective_matrix(V::DIM, width, height, projection),
+            view : view,
+            model: IDENTITY_MATRIX,
+        };
+        target
+            .draw(
+                self.fill_buffer.slice(0..self.fill_len).unwrap(),
+                glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList),
+                &self.fill_program,
+                &uniforms,
+                &draw_params,
+            )
+            .unwrap();
+    }
+    // draws a range of the vertex buffer into a viewport of the target
+    fn draw_range<V: VectorTrait>(
+        &self,
+        target: &mut glium::Frame,
+        range: Range<usize>,
+        viewport: glium::Rect,
+        view: Matrix4,
+        view_config: &ViewConfig,
+        color_mask: (bool, bool, bool, bool),
+    ) {
+        if range.is_empty() {
+            return;
+        }
+        let (width, height) = (viewport.width, viewport.height);
+        let draw_params = glium::DrawParameters {
+            smooth: Some(glium::draw_parameters::Smooth::Nicest),
+            blend: glium::Blend::alpha_blending(), //lines are a lot darker
+            line_width: Some(X::line_thickness(V::DIM, &view_config.lines)),
+            color_mask,
+            viewport: Some(viewport),
+            ..Default::default()
+        };
+        let uniforms = uniform! {
+            perspective : build_perspective_matrix(V::DIM, width, height, view_config.volume_projection),
+            view : view,
+            model: IDENTITY_MATRIX,
+            // below needed only for proj_line vertex
+            aspect : (width as f32)/(height as f32),
+            thickness : X::line_thickness(V::DIM, &view_config.lines),
+            miter : 1,
+            antialias : view_config.lines.antialias,
+        };
+        target
+            .draw(
+                self.vertex_buffer.slice(range).unwrap(),
+                glium::index::NoIndices(X::PRIMITIVE_TYPE),
+                &self.program,
+                &uniforms,
+                &draw_params,
+            )
+            .unwrap();
     }
 }
 
@@ -104,32 +241,37 @@ fn new_vertex_buffer<X: VertexTrait, V: VectorTrait>(
     glium::VertexBuffer::dynamic(display, &verts_to_gl(verts)).unwrap()
 }
 
-fn new_vertex_buffer_from_lines<X: VertexTrait, V: VectorTrait>(
-    lines: &[DrawLine<V>],
-    display: &Display,
-) -> VertexBuffer<X> {
-    let vertexes = opt_lines_to_gl(lines);
-    glium::VertexBuffer::dynamic(display, &vertexes).unwrap()
+// the contents are written when drawing
+fn new_empty_vertex_buffer<X: VertexTrait>(lines_len: usize, display: &Display) -> VertexBuffer<X> {
+    glium::VertexBuffer::dynamic(display, &vec![X::NO_DRAW; lines_len * X::LINE_BUFFER_SIZE])
+        .unwrap()
 }
 
+// writes each group of lines after the last, returning the range of vertices for each group
 fn write_opt_lines_to_buffer<X: VertexTrait, V: VectorTrait>(
     write_map: &mut WriteMapping<[X]>,
     buffer_len: usize,
-    opt_lines: &[DrawLine<V>],
-) {
+    opt_line_groups: &[&[DrawLine<V>]],
+) -> Vec<Range<usize>> {
     // TODO: this could be refactored with flat_map etc but i don't know how that impacts performance
     // TODO: is it faster to call .write once rather than write_map.set a bunch of times?
     let mut i = 0;
-    for opt_line in opt_lines.iter() {
-        for v in X::line_to_gl_iter(opt_line) {
-            write_map.set(i, v);
-            i += 1;
+    let mut ranges = Vec::with_capacity(opt_line_groups.len());
+    for opt_lines in opt_line_groups {
+        let start = i;
+        for opt_line in opt_lines.iter() {
+            for v in X::line_to_gl_iter(opt_line) {
+                write_map.set(i, v);
+                i += 1;
+            }
         }
+        ranges.push(start..i);
     }
     // Set remaining buffer with NO_DRAW verts to avoid "ghosts" while keeping buffer len unchanged
     for j in i..buffer_len {
         write_map.set(j, X::NO_DRAW);
     }
+    ranges
 }
 
 impl<X: VertexTrait> GraphicsTrait for Graphics<X> {
@@ -137,14 +279,13 @@ impl<X: VertexTrait> GraphicsTrait for Graphics<X> {
         Self::new(display)
     }
 
-    fn update_buffer<V: VectorTrait>(&mut self, draw_lines: &[DrawLine<V>], display: &Display) {
+    fn update_buffer(&mut self, draw_lines_len: usize, display: &Display) {
         //make new buffer if
         // a. the number of lines increases (need more room in the buffer)
         // b. the number of lines drastically decreases (to not waste memory)
         let cur_lines_len = self.cur_lines_len;
-        let draw_lines_len = draw_lines.len();
         if (draw_lines_len > cur_lines_len) | (draw_lines_len < cur_lines_len / 2) {
-            self.vertex_buffer = new_vertex_buffer_from_lines(draw_lines, display);
+            self.vertex_buffer = new_empty_vertex_buffer(draw_lines_len, display);
             // println!(
             //     "New buffer! {} to {}",
             //     self.cur_lines_len, draw_lines_len
@@ -153,49 +294,101 @@ impl<X: VertexTrait> GraphicsTrait for Graphics<X> {
         }
     }
 
+    fn update_fill_buffer<V: VectorTrait>(
+        &mut self,
+        triangles: &[DrawTriangle<V>],
+        display: &Display,
+    ) {
+        //like the line buffer, make a new buffer only if the fills outgrow it, or shrink drastically
+        let fill_len = triangles.len() * 3;
+        if (fill_len > self.fill_capacity) | (fill_len < self.fill_capacity / 2) {
+            self.fill_buffer = glium::VertexBuffer::empty_dynamic(display, fill_len).unwrap();
+            self.fill_capacity = fill_len;
+        }
+        self.fill_len = fill_len;
+        if fill_len == 0 {
+            return;
+        }
+        let mut write_map = self.fill_buffer.map_write();
+        let verts = triangles.iter().flat_map(|triangle| {
+            triangle.verts.map(|vertex| {
+                SimpleVertex::vert_to_gl(&DrawVertex {
+                    vertex,
+                    color: triangle.color,
+                })
+            })
+        });
+        for (i, vert) in verts.enumerate() {
+            write_map.set(i, vert);
+        }
+    }
+
     fn draw_lines<V: VectorTrait>(
         &mut self,
         draw_lines: &[DrawLine<V>],
+        view_config: &ViewConfig,
+        insets: &[InsetLines<V>],
         mut target: glium::Frame,
     ) -> glium::Frame {
+        let line_groups: Vec<&[DrawLine<V>]> = std::iter::once(draw_lines)
+            .chain(insets.iter().map(|inset| inset.lines))
+            .collect();
         //self.get_vertex_buffer().write(&Self::opt_lines_to_gl(&draw_lines));
-        write_opt_lines_to_buffer(
+        let ranges = write_opt_lines_to_buffer(
             &mut self.vertex_buffer.map_write(),
             self.cur_lines_len * X::LINE_BUFFER_SIZE,
-            draw_lines,
+            &line_groups,
         ); //slightly faster than the above (less allocation)
 
-        let draw_params = glium::DrawParameters {
-            smooth: Some(glium::draw_parameters::Smooth::Nicest),
-            blend: glium::Blend::alpha_blending(), //lines are a lot darker
-            line_width: Some(X::line_thickness(V::DIM)),
-            ..Default::default()
-        };
-        let (width, height) = target.get_dimensions();
-        let uniforms = uniform! {
-            perspective : build_perspective_matrix(V::DIM, width, height),
-            view : build_view_matrix(V::DIM),
-            model: IDENTITY_MATRIX,
-            // below needed only for proj_line vertex
-            aspect : (width as f32)/(height as f32),
-            thickness : X::line_thickness(V::DIM),
-            miter : 1,
-        };
+        let (target_width, height) = target.get_dimensions();
         target.clear_color(
             BACKGROUND_COLOR[0],
             BACKGROUND_COLOR[1],
             BACKGROUND_COLOR[2],
             BACKGROUND_COLOR[3],
         );
-        target
-            .draw(
-                &self.vertex_buffer,
-                glium::index::NoIndices(X::PRIMITIVE_TYPE),
-                &self.program,
-                &uniforms,
-                &draw_params,
-            )
-            .unwrap();
+        // the main lines are drawn once per eye, over the filled faces
+        for eye_view in eye_views(V::DIM, &view_config.stereo) {
+            let viewport = eye_view.viewport(target_width, height);
+            self.draw_fills::<V>(
+                &mut target,
+                viewport,
+                eye_view.view,
+                view_config.volume_projection,
+                eye_view.color_mask,
+            );
+            self.draw_range::<V>(
+                &mut target,
+                ranges[0].clone(),
+                viewport,
+                eye_view.view,
+                view_config,
+                eye_view.color_mask,
+            );
+        }
+        for (inset, range) in insets.iter().zip(ranges[1..].iter()) {
+            let viewport = viewport_rect(&inset.rect, target_width, height);
+            target.clear(
+                Some(&viewport),
+                Some((
+                    BACKGROUND_COLOR[0],
+                    BACKGROUND_COLOR[1],
+                    BACKGROUND_COLOR[2],
+                    BACKGROUND_COLOR[3],
+                )),
+                false,
+                None,
+                None,
+            );
+            self.draw_range::<V>(
+                &mut target,
+                range.clone(),
+                viewport,
+                build_view_matrix(V::DIM),
+                view_config,
+                (true, true, true, true),
+            );
+        }
 
         target
     }
@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 use crate::vector::scalar_linterp;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize, Debug)]
 pub struct Color(pub [f32; 4]);
 impl Color {
     pub const fn get_arr(&self) -> &[f32; 4] {
@@ -14,6 +16,15 @@ impl Color {
         arr[3] = alpha;
         Color(arr)
     }
+    // multiplies each channel by the tint's
+    pub fn tint(self, tint: Color) -> Color {
+        Color(std::array::from_fn(|i| self.0[i] * tint.0[i]))
+    }
+    // scales the rgb channels, keeping alpha
+    pub fn shade(self, brightness: f32) -> Color {
+        let [r, g, b, a] = self.0;
+        Color([r * brightness, g * brightness, b * brightness, a])
+    }
 }
 
 pub fn blend(color_1: Color, color_2: Color, t: f32) -> Color {
@@ -41,3 +52,56 @@ pub const YELLOW: Color = Color([1.0, 1.0, 0.0, 1.0]);
 pub const ORANGE: Color = Color([1.0, 0.5, 0.0, 1.0]);
 
 pub const DEFAULT_COLOR: Color = WHITE;
+
+// maps a value in [0, 1] to a color by blending evenly spaced color stops
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum Colormap {
+    Rainbow,
+    Thermal,
+    Diverging,
+    Custom(Vec<[f32; 3]>),
+}
+impl Colormap {
+    fn stops(&self) -> &[[f32; 3]] {
+        match self {
+            Colormap::Rainbow => &[
+                [0.0, 0.0, 1.0],
+                [0.0, 1.0, 1.0],
+                [0.0, 1.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [1.0, 0.0, 0.0],
+            ],
+            Colormap::Thermal => &[
+                [0.3, 0.0, 0.6],
+                [0.9, 0.0, 0.3],
+                [1.0, 0.5, 0.0],
+                [1.0, 1.0, 0.0],
+                [1.0, 1.0, 1.0],
+            ],
+            Colormap::Diverging => &[[0.0, 0.3, 1.0], [1.0, 1.0, 1.0], [1.0, 0.2, 0.0]],
+            Colormap::Custom(stops) => stops,
+        }
+    }
+    pub fn color(&self, t: f32) -> Color {
+        let stops = self.stops();
+        let rgb = |[r, g, b]: [f32; 3]| Color([r, g, b, 1.0]);
+        match stops.len() {
+            0 => DEFAULT_COLOR,
+            1 => rgb(stops[0]),
+            n => {
+                let x = t.clamp(0.0, 1.0) * (n - 1) as f32;
+                let i = (x.floor() as usize).min(n - 2);
+                blend(rgb(stops[i]), rgb(stops[i + 1]), x - i as f32)
+            }
+        }
+    }
+}
+
+#[test]
+fn test_colormap_endpoints() {
+    let colormap = Colormap::Custom(vec![[0.0, 0.0, 0.0], [1.0, 0.5, 0.0], [1.0, 1.0, 1.0]]);
+    assert_eq!(colormap.color(-1.0).0, BLACK.0);
+    assert_eq!(colormap.color(0.25).0, [0.5, 0.25, 0.0, 1.0]);
+    assert_eq!(colormap.color(0.5).0, [1.0, 0.5, 0.0, 1.0]);
+    assert_eq!(colormap.color(2.0).0, WHITE.0);
+}
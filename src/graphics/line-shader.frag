@@ -0,0 +1,23 @@
+#version 140
+
+in vec4 in_color;
+in float in_edge;
+in float in_distance;
+in vec2 in_dash;
+out vec4 color;
+
+uniform bool antialias;
+
+void main() {
+    // dashes: a period of 0 draws a solid line
+    if (in_dash.y > 0.0 && mod(in_distance, in_dash.y) > in_dash.x) {
+        discard;
+    }
+    float alpha = in_color.a;
+    if (antialias) {
+        // fade out over about a pixel at each side of the line
+        float edge_width = fwidth(in_edge);
+        alpha *= 1.0 - smoothstep(1.0 - 2.0 * edge_width, 1.0, abs(in_edge));
+    }
+    color = vec4(in_color.rgb, alpha);
+}
@@ -1,5 +1,6 @@
 use std::f32::consts::PI;
 
+use crate::draw::Projection;
 use crate::vector::VecIndex;
 
 pub type Matrix4 = [[f32; 4]; 4];
@@ -11,18 +12,63 @@ pub const IDENTITY_MATRIX: Matrix4 = [
     [0.0, 0.0, 0.0, 1.0f32],
 ];
 
+const FOV_3D: f32 = PI / 16.0;
+const ZFAR_3D: f32 = 100.0;
+
+const VIEW_POSITION_3D: [f32; 3] = [2.0, 2.0, -4.0];
+const VIEW_DIRECTION_3D: [f32; 3] = [-1.0, -1.0, 2.0];
+const VIEW_UP_3D: [f32; 3] = [0.0, 1.0, 0.0];
+
 pub fn build_view_matrix(dim: VecIndex) -> Matrix4 {
     match dim {
         2 => IDENTITY_MATRIX,
-        3 => build_view_matrix_3d(&[2.0, 2.0, -4.0], &[-1.0, -1.0, 2.0], &[0.0, 1.0, 0.0]),
+        3 => build_view_matrix_3d(&VIEW_POSITION_3D, &VIEW_DIRECTION_3D, &VIEW_UP_3D),
         _ => panic!("Invalid dimension"),
     }
 }
 
-pub fn build_perspective_matrix(dim: VecIndex, width: u32, height: u32) -> Matrix4 {
+// view matrix for an eye displaced by eye_offset along the screen horizontal,
+// turned to look at the point at distance convergence along the view direction
+pub fn build_eye_view_matrix(dim: VecIndex, eye_offset: f32, convergence: f32) -> Matrix4 {
     match dim {
-        2 => build_perspective_mat_2d(width, height),
-        3 => build_perspective_mat_3d(width, height),
+        2 => IDENTITY_MATRIX,
+        3 => {
+            let (p, d, up) = (VIEW_POSITION_3D, VIEW_DIRECTION_3D, VIEW_UP_3D);
+            let d_len = (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt();
+            // same as the s vector in build_view_matrix_3d
+            let s = [
+                up[1] * d[2] - up[2] * d[1],
+                up[2] * d[0] - up[0] * d[2],
+                up[0] * d[1] - up[1] * d[0],
+            ];
+            let s_len = (s[0] * s[0] + s[1] * s[1] + s[2] * s[2]).sqrt();
+            let eye: [f32; 3] = std::array::from_fn(|i| p[i] + s[i] * eye_offset / s_len);
+            let target: [f32; 3] = std::array::from_fn(|i| p[i] + d[i] * convergence / d_len);
+            let direction: [f32; 3] = std::array::from_fn(|i| target[i] - eye[i]);
+            build_view_matrix_3d(&eye, &direction, &up)
+        }
+        _ => panic!("Invalid dimension"),
+    }
+}
+
+// matrices are column major, as passed to the shaders
+pub fn transform_point(matrix: &Matrix4, point: [f32; 4]) -> [f32; 4] {
+    std::array::from_fn(|row| (0..4).map(|col| matrix[col][row] * point[col]).sum())
+}
+
+pub fn build_perspective_matrix(
+    dim: VecIndex,
+    width: u32,
+    height: u32,
+    projection: Projection,
+) -> Matrix4 {
+    match (dim, projection) {
+        (2, _) => build_perspective_mat_2d(width, height),
+        (3, Projection::Perspective) => build_perspective_mat_3d(width, height),
+        (3, Projection::Orthographic) => build_oblique_mat_3d(width, height, 0.0, 0.0),
+        (3, Projection::Oblique { angle, depth_scale }) => {
+            build_oblique_mat_3d(width, height, angle, depth_scale)
+        }
         _ => panic!("Invalid dimension"),
     }
 }
@@ -87,9 +133,9 @@ pub fn build_perspective_mat_3d(width: u32, height: u32) -> Matrix4 {
     let aspect_ratio = height as f32 / width as f32;
     //let fov: f32 = 3.141592 / 3.0; //nearly fish eye
     //let fov : f32 = 3.141592 / 8.0;
-    let fov: f32 = PI / 16.0; //comparable to 3d
-                              //let zfar = 1024.0;/
-    let zfar = 100.0;
+    let fov: f32 = FOV_3D; //comparable to 3d
+                           //let zfar = 1024.0;/
+    let zfar = ZFAR_3D;
     let znear = 0.1;
 
     let f = 1.0 / (fov / 2.0).tan();
@@ -100,3 +146,21 @@ pub fn build_perspective_mat_3d(width: u32, height: u32) -> Matrix4 {
         [0., 0., 0., 1.032f32],
     ]
 }
+
+// orthographic, at the scale the perspective matrix has at the center of the volume.
+// depth from the center shears the image along angle
+fn build_oblique_mat_3d(width: u32, height: u32, angle: f32, depth_scale: f32) -> Matrix4 {
+    let aspect_ratio = height as f32 / width as f32;
+    let p = VIEW_POSITION_3D;
+    let center_depth = (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt();
+    // perspective w at the center
+    let f = 1.0 / (FOV_3D / 2.0).tan() / (center_depth + 1.032);
+    let (fx, fy) = (f * aspect_ratio, f);
+    let (sx, sy) = (angle.cos() * depth_scale, angle.sin() * depth_scale);
+    [
+        [fx, 0., 0., 0.],
+        [0., fy, 0., 0.],
+        [fx * sx, fy * sy, 1.0 / ZFAR_3D, 0.],
+        [-fx * sx * center_depth, -fy * sy * center_depth, 0., 1.0f32],
+    ]
+}
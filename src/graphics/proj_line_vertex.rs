@@ -3,13 +3,11 @@ use std::array::IntoIter;
 use glium::index::PrimitiveType;
 use itertools::Itertools;
 
+use crate::config::LineConfig;
 use crate::vector::VecIndex;
 
 use super::{DrawLine, DrawVertex, VectorTrait, VertexTrait};
 
-const LINE_THICKNESS_3D: f32 = 0.01;
-const LINE_THICKNESS_4D: f32 = 0.02;
-
 #[derive(Copy, Clone)]
 pub struct ProjLineVertex {
     pub position: [f32; 3],
@@ -17,8 +15,21 @@ pub struct ProjLineVertex {
     pub direction: f32,
     pub next: [f32; 3],
     pub previous: [f32; 3],
+    // distance from the start of the line, and the dash pattern of its style.
+    // a period of 0 draws a solid line
+    pub distance: f32,
+    pub dash: [f32; 2],
 }
-implement_vertex!(ProjLineVertex, position, color, direction, next, previous);
+implement_vertex!(
+    ProjLineVertex,
+    position,
+    color,
+    direction,
+    next,
+    previous,
+    distance,
+    dash
+);
 
 impl Default for ProjLineVertex {
     fn default() -> Self {
@@ -33,6 +44,8 @@ fn line_to_gl_arr<V: VectorTrait>(draw_line: &DrawLine<V>) -> [ProjLineVertex; 6
     //let dir = draw_verts[1].vertex - draw_verts[0].vertex;
     //let midpoint = crate::vector::VectorTrait::linterp(draw_verts[0].vertex,draw_verts[1].vertex,0.5);
     let proj_verts = draw_verts.map(|dv| ProjLineVertex::project_pos(dv.vertex));
+    let length = (draw_verts[1].vertex - draw_verts[0].vertex).norm();
+    let dash = draw_line.style.dash_pattern().unwrap_or([0.0, 0.0]);
     //draw two triangles to make a line
     // if we really need to, we can have this return a fixed len array
     [(0, -1), (0, 1), (1, -1), (1, -1), (1, 1), (0, 1)].map(|(i, d)| ProjLineVertex {
@@ -41,6 +54,8 @@ fn line_to_gl_arr<V: VectorTrait>(draw_line: &DrawLine<V>) -> [ProjLineVertex; 6
         color: *draw_verts[i].color.get_arr(),
         next: proj_verts[1],
         previous: proj_verts[0],
+        distance: i as f32 * length,
+        dash,
     })
 }
 
@@ -57,6 +72,7 @@ impl VertexTrait for ProjLineVertex {
     const LINE_BUFFER_SIZE: usize = 6;
     const PRIMITIVE_TYPE: PrimitiveType = PrimitiveType::TrianglesList;
     const VERTEX_SHADER_SRC: &'static str = include_str!("test-shader.vert");
+    const FRAGMENT_SHADER_SRC: &'static str = include_str!("line-shader.frag");
     //make this consume its input?
     const NO_DRAW: Self = Self {
         position: [0., 0., 0.],
@@ -64,11 +80,13 @@ impl VertexTrait for ProjLineVertex {
         direction: 0.,
         next: [0., 0., 0.],
         previous: [0., 0., 0.],
+        distance: 0.,
+        dash: [0., 0.],
     };
-    fn line_thickness(dim: VecIndex) -> f32 {
+    fn line_thickness(dim: VecIndex, line_config: &LineConfig) -> f32 {
         match dim {
-            2 => LINE_THICKNESS_3D,
-            3 => LINE_THICKNESS_4D,
+            2 => line_config.width_3d,
+            3 => line_config.width_4d,
             _ => panic!("Invalid dimension"),
         }
     }
@@ -81,6 +99,8 @@ impl VertexTrait for ProjLineVertex {
             color: *vert.color.get_arr(),
             next: pos,
             previous: pos,
+            distance: 0.0,
+            dash: [0.0, 0.0],
         }
     }
     fn line_to_gl<V: VectorTrait>(draw_line: &DrawLine<V>) -> Vec<Self> {
@@ -3,13 +3,16 @@ use std::array::IntoIter;
 use glium::index::PrimitiveType;
 use itertools::Itertools;
 
+use crate::config::LineConfig;
 use crate::vector::VecIndex;
 
 use super::{DrawLine, DrawVertex, VectorTrait, VertexTrait};
 
 pub const VERTEX_SHADER_SRC: &str = include_str!("simple-shader.vert");
+pub const FRAGMENT_SHADER_SRC: &str = include_str!("simple-shader.frag");
 
-// thicknesses greater than 1.0 seem to have no effect
+// thicknesses greater than 1.0 seem to have no effect, so the configured widths are ignored.
+// these lines are not dashed either
 const LINE_THICKNESS_3D: f32 = 1.0;
 const LINE_THICKNESS_4D: f32 = 1.0;
 // This, for some time now, cannot be used in place of NewVertex
@@ -31,13 +34,14 @@ fn line_to_gl_arr<V: VectorTrait>(line: &DrawLine<V>) -> [SimpleVertex; 2] {
 impl VertexTrait for SimpleVertex {
     const PRIMITIVE_TYPE: PrimitiveType = PrimitiveType::LinesList;
     const VERTEX_SHADER_SRC: &'static str = VERTEX_SHADER_SRC;
+    const FRAGMENT_SHADER_SRC: &'static str = FRAGMENT_SHADER_SRC;
     type Iter = IntoIter<Self, 2>;
     //make this consume its input?
     const NO_DRAW: Self = Self {
         position: [0.0, 0.0, 10.0],
         color: [1.0, 0.0, 0.0, 1.0f32],
     };
-    fn line_thickness(dim: VecIndex) -> f32 {
+    fn line_thickness(dim: VecIndex, _line_config: &LineConfig) -> f32 {
         match dim {
             2 => LINE_THICKNESS_3D,
             3 => LINE_THICKNESS_4D,
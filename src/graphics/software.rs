@@ -0,0 +1,193 @@
+use glium::Rect;
+
+use crate::config::ViewConfig;
+use crate::constants::BACKGROUND_COLOR;
+use crate::draw::DrawLine;
+use crate::graphics::colors::blend;
+use crate::vector::{Field, VecIndex, VectorTrait};
+
+use super::matrices::{build_perspective_matrix, transform_point};
+use super::stereo::eye_views;
+
+// draws lines into an rgba pixel buffer on the cpu, with the same matrices and eye views as Graphics,
+// so that rendering can be checked without a gpu. lines are one pixel wide and not antialiased.
+// dashes follow the line style. rows start from the bottom of the image, as in gl
+pub struct SoftwareGraphics {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<[f32; 4]>,
+}
+impl SoftwareGraphics {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![BACKGROUND_COLOR; (width * height) as usize],
+        }
+    }
+    pub fn pixel(&self, x: u32, y: u32) -> [f32; 4] {
+        self.pixels[(y * self.width + x) as usize]
+    }
+    pub fn draw_lines<V: VectorTrait>(
+        &mut self,
+        draw_lines: &[DrawLine<V>],
+        view_config: &ViewConfig,
+    ) {
+        self.pixels.fill(BACKGROUND_COLOR);
+        for eye_view in eye_views(V::DIM, &view_config.stereo) {
+            let viewport = eye_view.viewport(self.width, self.height);
+            let perspective = build_perspective_matrix(
+                V::DIM,
+                viewport.width,
+                viewport.height,
+                view_config.volume_projection,
+            );
+            let to_pixel = |v: V| {
+                let point = std::array::from_fn(|i| match i {
+                    3 => 1.0,
+                    i if i < V::DIM as usize => v[i as VecIndex],
+                    _ => 0.0,
+                });
+                let clip = transform_point(&perspective, transform_point(&eye_view.view, point));
+                (clip[3] > 0.0).then(|| {
+                    [
+                        viewport.left as f32
+                            + (clip[0] / clip[3] + 1.0) / 2.0 * viewport.width as f32,
+                        (clip[1] / clip[3] + 1.0) / 2.0 * viewport.height as f32,
+                    ]
+                })
+            };
+            for draw_line in draw_lines {
+                let [v0, v1] = draw_line.get_draw_verts();
+                let length = (v1.vertex - v0.vertex).norm();
+                if let (Some(p0), Some(p1)) = (to_pixel(v0.vertex), to_pixel(v1.vertex)) {
+                    let n_steps = Field::max((p1[0] - p0[0]).abs(), (p1[1] - p0[1]).abs()).ceil();
+                    for step in 0..=(n_steps as usize) {
+                        let t = if n_steps > 0.0 {
+                            step as f32 / n_steps
+                        } else {
+                            0.0
+                        };
+                        if !draw_line.style.is_drawn_at(t * length) {
+                            continue;
+                        }
+                        let color = blend(v0.color, v1.color, t);
+                        self.blend_pixel(
+                            [p0[0] + (p1[0] - p0[0]) * t, p0[1] + (p1[1] - p0[1]) * t],
+                            *color.get_arr(),
+                            eye_view.color_mask,
+                            &viewport,
+                        );
+                    }
+                }
+            }
+        }
+    }
+    // alpha blends a color into the pixel containing pos, writing only the masked channels
+    fn blend_pixel(
+        &mut self,
+        pos: [f32; 2],
+        color: [f32; 4],
+        color_mask: (bool, bool, bool, bool),
+        viewport: &Rect,
+    ) {
+        let (x, y) = (pos[0].floor(), pos[1].floor());
+        let in_viewport = x >= viewport.left as f32
+            && x < (viewport.left + viewport.width) as f32
+            && y >= viewport.bottom as f32
+            && y < (viewport.bottom + viewport.height) as f32;
+        if !in_viewport {
+            return;
+        }
+        let mask = [color_mask.0, color_mask.1, color_mask.2, color_mask.3];
+        let pixel = &mut self.pixels[(y as u32 * self.width + x as u32) as usize];
+        let alpha = color[3];
+        for i in (0..4).filter(|&i| mask[i]) {
+            pixel[i] = color[i] * alpha + pixel[i] * (1.0 - alpha);
+        }
+    }
+}
+
+#[cfg(test)]
+fn draw_vertical_line(view_config: &ViewConfig, style: crate::draw::LineStyle) -> SoftwareGraphics {
+    use crate::geometry::Line;
+    use crate::graphics::colors::WHITE;
+    use crate::vector::Vec3;
+
+    let mut graphics = SoftwareGraphics::new(200, 100);
+    let draw_line = DrawLine {
+        // behind the convergence point, so the eyes disagree on where it is
+        line: Line(Vec3::new(-0.4, -0.5, 0.8), Vec3::new(-0.4, -0.3, 0.8)),
+        color: WHITE,
+        end_color: None,
+        style,
+    };
+    graphics.draw_lines(&[draw_line], view_config);
+    graphics
+}
+
+#[cfg(test)]
+fn stereo_view_config(mode: crate::config::StereoMode) -> ViewConfig {
+    let mut view_config = ViewConfig::default();
+    view_config.stereo.mode = mode;
+    view_config
+}
+
+// columns where any pixel satisfies f
+#[cfg(test)]
+fn columns_where<F: Fn([f32; 4]) -> bool>(graphics: &SoftwareGraphics, f: F) -> Vec<u32> {
+    (0..graphics.width)
+        .filter(|&x| (0..graphics.height).any(|y| f(graphics.pixel(x, y))))
+        .collect()
+}
+
+#[test]
+fn test_anaglyph_eyes() {
+    use crate::config::StereoMode;
+    use crate::draw::LineStyle;
+    let bg = BACKGROUND_COLOR;
+    let mono = draw_vertical_line(&stereo_view_config(StereoMode::Off), LineStyle::Solid);
+    let mono_cols = columns_where(&mono, |p| p[0] > bg[0] && p[1] > bg[1]);
+    assert!(!mono_cols.is_empty());
+
+    let anaglyph = draw_vertical_line(&stereo_view_config(StereoMode::Anaglyph), LineStyle::Solid);
+    // each eye writes only its own channels, and the eyes see the line in different places
+    let red_cols = columns_where(&anaglyph, |p| p[0] > bg[0] && p[1] == bg[1]);
+    let cyan_cols = columns_where(&anaglyph, |p| p[0] == bg[0] && p[1] > bg[1] && p[2] > bg[2]);
+    assert!(!red_cols.is_empty() && !cyan_cols.is_empty());
+    assert!(red_cols.iter().all(|x| !cyan_cols.contains(x)));
+}
+
+#[test]
+fn test_side_by_side_eyes() {
+    use crate::config::StereoMode;
+    use crate::draw::LineStyle;
+    let bg = BACKGROUND_COLOR;
+    let graphics = draw_vertical_line(
+        &stereo_view_config(StereoMode::SideBySide),
+        LineStyle::Solid,
+    );
+    let cols = columns_where(&graphics, |p| p[0] > bg[0]);
+    let half = graphics.width / 2;
+    assert!(cols.iter().any(|&x| x < half));
+    assert!(cols.iter().any(|&x| x >= half));
+}
+
+#[test]
+fn test_dashed_line() {
+    use crate::config::StereoMode;
+    use crate::draw::LineStyle;
+    let bg = BACKGROUND_COLOR;
+    // rows of the line's column that are drawn
+    let drawn_rows = |style| {
+        let graphics = draw_vertical_line(&stereo_view_config(StereoMode::Off), style);
+        let x = columns_where(&graphics, |p| p[0] > bg[0])[0];
+        (0..graphics.height)
+            .filter(|&y| graphics.pixel(x, y)[0] > bg[0])
+            .count()
+    };
+    let solid = drawn_rows(LineStyle::Solid);
+    let dashed = drawn_rows(LineStyle::Dashed);
+    let dotted = drawn_rows(LineStyle::Dotted);
+    assert!(0 < dotted && dotted < dashed && dashed < solid);
+}
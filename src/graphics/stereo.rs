@@ -0,0 +1,53 @@
+use crate::config::{StereoConfig, StereoMode};
+use crate::draw::split_view::ScreenRect;
+use crate::vector::VecIndex;
+
+use super::matrices::{build_eye_view_matrix, build_view_matrix, Matrix4};
+use super::viewport_rect;
+
+const ALL_CHANNELS: (bool, bool, bool, bool) = (true, true, true, true);
+const RED_CHANNEL: (bool, bool, bool, bool) = (true, false, false, true);
+const CYAN_CHANNELS: (bool, bool, bool, bool) = (false, true, true, true);
+
+// one pass over the line buffer
+pub struct EyeView {
+    pub view: Matrix4,
+    pub color_mask: (bool, bool, bool, bool),
+    pub rect: ScreenRect,
+}
+impl EyeView {
+    // left and width are the horizontal extent of the eye's viewport, as fractions of the target width
+    fn new(view: Matrix4, color_mask: (bool, bool, bool, bool), left: f32, width: f32) -> Self {
+        Self {
+            view,
+            color_mask,
+            rect: ScreenRect {
+                left,
+                bottom: 0.0,
+                width,
+                height: 1.0,
+            },
+        }
+    }
+    pub fn viewport(&self, target_width: u32, target_height: u32) -> glium::Rect {
+        viewport_rect(&self.rect, target_width, target_height)
+    }
+}
+
+// stereo only applies to the projected volume, so the 2d view of the 3d mode is always drawn once.
+// side by side views are for parallel viewing (left eye on the left)
+pub fn eye_views(dim: VecIndex, stereo: &StereoConfig) -> Vec<EyeView> {
+    let half_sep = stereo.eye_separation / 2.0;
+    let eye = |offset| build_eye_view_matrix(dim, offset, stereo.convergence);
+    match (dim, stereo.mode) {
+        (3, StereoMode::Anaglyph) => vec![
+            EyeView::new(eye(-half_sep), RED_CHANNEL, 0.0, 1.0),
+            EyeView::new(eye(half_sep), CYAN_CHANNELS, 0.0, 1.0),
+        ],
+        (3, StereoMode::SideBySide) => vec![
+            EyeView::new(eye(-half_sep), ALL_CHANNELS, 0.0, 0.5),
+            EyeView::new(eye(half_sep), ALL_CHANNELS, 0.5, 0.5),
+        ],
+        _ => vec![EyeView::new(build_view_matrix(dim), ALL_CHANNELS, 0.0, 1.0)],
+    }
+}
@@ -7,7 +7,12 @@ in vec4 color;
 in float direction; 
 in vec3 next;
 in vec3 previous;
+in float distance;
+in vec2 dash;
 out vec4 in_color;
+out float in_edge;
+out float in_distance;
+out vec2 in_dash;
 uniform mat4 perspective;
 uniform mat4 view;
 uniform mat4 model;
@@ -54,4 +59,7 @@ void main() {
   gl_PointSize = 1.0;
 
   in_color = color;
+  in_edge = direction;
+  in_distance = distance;
+  in_dash = dash;
 }
\ No newline at end of file
@@ -1,40 +1,107 @@
-use crate::components::MoveNext;
+use crate::collide::Contacts;
+use crate::components::{MoveNext, Player};
+use crate::config::{Config, PhysicsConfig};
 use crate::ecs_utils::Componentable;
+use crate::input::key_map::JUMP;
 use crate::input::Input;
-use crate::vector::VectorTrait;
+use crate::vector::{Field, VectorTrait};
 use specs::prelude::*;
-use specs::{Read, System, WriteStorage};
+use specs::{Component, HashMapStorage, Read, System, WriteStorage};
 use std::marker::PhantomData;
 
+// vertical motion of the player. gravity pulls along -one_hot(1)
+#[derive(Component, Default)]
+#[storage(HashMapStorage)]
+pub struct VerticalMotion {
+    // upwards speed
+    pub velocity: Field,
+    // the floor the player stood on during the last collision check
+    pub ground: Option<Entity>,
+}
+impl VerticalMotion {
+    // the vertical step for this frame. the player jumps when grounded,
+    // otherwise falls faster until reaching terminal velocity
+    pub fn step(&mut self, config: &PhysicsConfig, jump: bool, dt: Field) -> Field {
+        if self.ground.is_some() && jump {
+            self.velocity = config.jump_speed;
+        }
+        self.velocity = (self.velocity - config.gravity * dt).max(-config.terminal_velocity);
+        self.velocity * dt
+    }
+    // update from the collision check. landing stops the fall, and hitting a ceiling stops the jump
+    pub fn land(&mut self, contacts: &Contacts) {
+        self.ground = contacts.ground;
+        if (self.ground.is_some() && self.velocity < 0.0)
+            || (contacts.ceiling && self.velocity > 0.0)
+        {
+            self.velocity = 0.0;
+        }
+    }
+}
+
 pub struct PlayerGravitySystem<V>(pub PhantomData<V>);
 
 impl<'a, V: VectorTrait + Componentable> System<'a> for PlayerGravitySystem<V> {
     type SystemData = (
         Read<'a, Input>,
-        WriteStorage<'a, MoveNext<V>>, //gravity should apply even if the player isn't moving, but debug for now
+        ReadExpect<'a, Player>,
+        ReadExpect<'a, Config>,
+        WriteStorage<'a, VerticalMotion>,
+        WriteStorage<'a, MoveNext<V>>,
     );
 
-    fn run(&mut self, (input, mut write_move_next): Self::SystemData) {
-        for move_next in (&mut write_move_next).join() {
-            let gvec = -V::one_hot(1) * input.get_dt() * 0.0;
-            match move_next {
-                MoveNext {
-                    next_dpos: Some(next_dpos),
-                    can_move: Some(true),
-                } => {
-                    move_next.next_dpos = Some(*next_dpos + gvec);
-                }
-                MoveNext {
-                    next_dpos: None,
-                    can_move: Some(true),
-                } => {
-                    *move_next = MoveNext {
-                        next_dpos: Some(gvec),
-                        can_move: Some(true),
-                    }
-                }
-                _ => (),
-            }
+    fn run(
+        &mut self,
+        (input, player, config, mut vertical_motions, mut write_move_next): Self::SystemData,
+    ) {
+        let (Some(motion), Some(move_next)) = (
+            vertical_motions.get_mut(player.0),
+            write_move_next.get_mut(player.0),
+        ) else {
+            return;
+        };
+        if move_next.can_move != Some(true) {
+            return;
+        }
+        // flying keeps the free movement of the editor
+        if config.physics.fly {
+            *motion = VerticalMotion::default();
+            return;
         }
+        // walking moves only along the ground, and gravity takes over vertical movement
+        let up = V::one_hot(1);
+        let walk = move_next
+            .next_dpos
+            .map(|dpos| dpos - up * dpos.dot(up))
+            .unwrap_or_else(V::zero);
+        let fall = motion.step(&config.physics, input.helper.key_held(JUMP), input.get_dt());
+        move_next.next_dpos = Some(walk + up * fall);
+    }
+}
+
+#[test]
+fn test_vertical_motion() {
+    let config = PhysicsConfig::default();
+    let dt = 0.01;
+    let floor = World::new().create_entity().build();
+    let mut motion = VerticalMotion::default();
+    // can't jump in midair
+    assert!(motion.step(&config, true, dt) < 0.0);
+    for _ in 0..10000 {
+        motion.step(&config, false, dt);
     }
+    assert_eq!(motion.velocity, -config.terminal_velocity);
+    // landing stops the fall, and the player can jump from the ground
+    motion.land(&Contacts {
+        ground: Some(floor),
+        ceiling: false,
+    });
+    assert_eq!(motion.velocity, 0.0);
+    assert!(motion.step(&config, true, dt) > 0.0);
+    // a ceiling stops the jump
+    motion.land(&Contacts {
+        ground: None,
+        ceiling: true,
+    });
+    assert_eq!(motion.velocity, 0.0);
 }
@@ -1,4 +1,5 @@
 use crate::components::*;
+use crate::draw::ViewportShape;
 use crate::ecs_utils::Componentable;
 use crate::fps::FPSFloat;
 use crate::geometry::shape::RefShapes;
@@ -20,6 +21,8 @@ use std::time::Instant;
 struct State {
     text: String,
     checked: bool,
+    // index of the viewport shape picked in the ui, until the engine applies it
+    viewport_choice: Option<usize>,
 }
 pub struct System {
     pub imgui: Context,
@@ -98,6 +101,8 @@ pub enum UIArgs {
         frame_duration: FPSFloat,
         coins_collected: u32,
         coins_left: u32,
+        // index of the current viewport shape in ViewportShape::NAMES
+        viewport_shape: usize,
     },
     Debug {
         frame_duration: FPSFloat,
@@ -183,6 +188,10 @@ impl UIArgs {
                 }
                 MaybeSelected(None) => "No selection\n".to_string(),
             },
+            format!(
+                "Shapes culled: {}\n",
+                world.read_resource::<crate::draw::ShapesCulled>().0
+            ),
             //crate::clipping::ShapeClipState::<V>::in_front_debug(world),
         ]
         .into_iter()
@@ -236,11 +245,11 @@ fn hello_world(_: &mut bool, ui: &mut Ui, ui_args: &mut UIArgs) {
             ));
         });
 }
-fn simple_ui(_: &mut bool, ui: &mut Ui, ui_args: &mut UIArgs) {
+fn simple_ui(_: &mut bool, ui: &mut Ui, ui_args: &mut UIArgs, state: &mut State) {
     use imgui::{Condition, Window};
     ui.window("Press M to toggle mouse control")
         .position([0., 0.], Condition::Appearing)
-        .size([190.0, 110.0], Condition::FirstUseEver)
+        .size([190.0, 135.0], Condition::FirstUseEver)
         .bg_alpha(0.75)
         .title_bar(false)
         .resizable(false)
@@ -263,6 +272,7 @@ fn simple_ui(_: &mut bool, ui: &mut Ui, ui_args: &mut UIArgs) {
                     ref frame_duration,
                     ref coins_collected,
                     ref coins_left,
+                    ref viewport_shape,
                 } => {
                     let total_coins = coins_left + coins_collected;
                     let coin_text = format!("Coins: {}/{}", coins_collected, total_coins);
@@ -273,6 +283,12 @@ fn simple_ui(_: &mut bool, ui: &mut Ui, ui_args: &mut UIArgs) {
                         .build(ui);
                     ui.text("Press M to toggle mouse");
                     ui.text("Backspace toggles 3D/4D");
+                    let mut index = *viewport_shape;
+                    if ui.combo_simple_string("Viewport", &mut index, &ViewportShape::NAMES)
+                        && index != *viewport_shape
+                    {
+                        state.viewport_choice = Some(index);
+                    }
                 }
                 _ => (),
             };
@@ -306,6 +322,10 @@ fn debug_ui(_: &mut bool, ui: &mut Ui, ui_args: &mut UIArgs, state: &mut State)
 }
 
 impl System {
+    // the viewport shape picked in the ui since the last call
+    pub fn take_viewport_choice(&mut self) -> Option<usize> {
+        self.state.viewport_choice.take()
+    }
     pub fn update<E>(
         &mut self,
         display: &Display,
@@ -349,7 +369,7 @@ impl System {
         let mut run = true;
         match self.ui_args {
             UIArgs::Debug { .. } => debug_ui(&mut run, ui, &mut self.ui_args, &mut self.state),
-            UIArgs::Simple { .. } => simple_ui(&mut run, ui, &mut self.ui_args),
+            UIArgs::Simple { .. } => simple_ui(&mut run, ui, &mut self.ui_args, &mut self.state),
             UIArgs::Test { .. } => hello_world(&mut run, ui, &mut self.ui_args),
             UIArgs::None => (),
         };
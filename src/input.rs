@@ -23,6 +23,7 @@ use winit_input_helper::WinitInputHelper;
 use specs::prelude::*;
 
 use crate::components::*;
+use crate::config::{HiddenAxisConfig, PhysicsConfig};
 use crate::vector::{Field, VecIndex, VectorTrait};
 
 use crate::geometry::shape::RefShapes;
@@ -30,6 +31,7 @@ use glutin::event::{Event, WindowEvent};
 
 use self::key_map::{
     MOVEMENT_MODE, PRINT_DEBUG, QUIT, TOGGLEABLE_KEYS, TOGGLE_CLIPPING, TOGGLE_DIMENSION,
+    TOGGLE_FLY, TOGGLE_HIDDEN_AXIS_COLORING,
 };
 
 // fn duration_as_field(duration : &Duration) -> f32 {
@@ -201,6 +203,22 @@ pub fn print_debug<V: VectorTrait>(input: &mut Input, clip_state: &mut ClipState
     }
 }
 
+pub fn toggle_hidden_axis_coloring(input: &mut Input, hidden_axis: &mut HiddenAxisConfig) {
+    if input.helper.key_released(TOGGLE_HIDDEN_AXIS_COLORING) {
+        hidden_axis.enabled = !hidden_axis.enabled;
+        println!("hidden axis coloring={}", hidden_axis.enabled);
+        input.update = true;
+    }
+}
+
+pub fn toggle_fly(input: &mut Input, physics: &mut PhysicsConfig) {
+    if input.helper.key_released(TOGGLE_FLY) {
+        physics.fly = !physics.fly;
+        println!("fly={}", physics.fly);
+        input.update = true;
+    }
+}
+
 impl Input {
     pub fn listen_inputs(&mut self) {
         self.toggle_keys.update_toggle_keys(&self.helper);
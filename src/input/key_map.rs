@@ -24,6 +24,7 @@ pub const DUPLICATE_SHAPE: VKC = VKC::Semicolon;
 pub const RESET_ORIENTATION: VKC = VKC::Comma;
 
 pub const TOGGLE_CLIPPING: VKC = VKC::C;
+pub const TOGGLE_HIDDEN_AXIS_COLORING: VKC = VKC::H;
 
 pub const QUIT: VKC = VKC::Escape;
 pub const TOGGLE_DIMENSION: VKC = VKC::Back;
@@ -38,7 +39,10 @@ pub const MOVE_KEYMAP: [(VKC, VKC, VecIndex); 3] = [
 pub const MOVE_FORWARDS: VKC = VKC::W;
 pub const MOVE_BACKWARDS: VKC = VKC::S;
 
-pub const PRINT_DEBUG: VKC = VKC::Space;
+pub const JUMP: VKC = VKC::Space;
+pub const TOGGLE_FLY: VKC = VKC::G;
+
+pub const PRINT_DEBUG: VKC = VKC::P;
 
 pub const TOGGLEABLE_KEYS: [VKC; 7] = [
     AXIS_KEYMAP[0].0,
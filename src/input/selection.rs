@@ -9,11 +9,12 @@ use super::{Input, MovementMode, PlayerMovementMode, ShapeMovementMode, MOUSE_SE
 
 use crate::cleanup::DeletedEntities;
 use crate::config::Config;
-use crate::constants::{CUBE_LABEL_STR, SELECTION_COLOR};
+use crate::constants::{CUBE_LABEL_STR, LABEL_HEIGHT, SELECTION_COLOR};
 use crate::draw::draw_line_collection::DrawLineCollection;
+use crate::draw::texture::text::{text_lines, text_width};
 use crate::draw::texture::{color_cube, color_cube_texture, fuzzy_color_cube_texture};
 use crate::draw::visual_aids::{calc_wireframe_lines, draw_axes};
-use crate::draw::ShapeTexture;
+use crate::draw::{LineStyle, ShapeTexture};
 use crate::ecs_utils::Componentable;
 use crate::geometry::transform::{self, Scaling};
 use crate::player::Player;
@@ -190,9 +191,36 @@ pub fn manipulate_shape<V: VectorTrait>(
     update
 }
 
-pub fn selection_box<V: VectorTrait>(shape: &Shape<V>) -> DrawLineCollection<V> {
-    DrawLineCollection::from_lines(calc_wireframe_lines(shape), SELECTION_COLOR)
-        .extend(draw_axes(barycenter(&shape.verts), 1.0))
+// wireframe and axes of the selected shape, with its label written above it
+pub fn selection_box<V: VectorTrait>(
+    shape: &Shape<V>,
+    label: Option<&ShapeLabel>,
+) -> DrawLineCollection<V> {
+    let center = barycenter(&shape.verts);
+    let selection_box =
+        DrawLineCollection::from_lines(calc_wireframe_lines(shape), SELECTION_COLOR)
+            .with_style(LineStyle::Dashed)
+            .extend(draw_axes(center, 1.0));
+    match label {
+        Some(label) => {
+            let top = shape
+                .verts
+                .iter()
+                .map(|v| v[1])
+                .fold(Field::NEG_INFINITY, Field::max);
+            let mut origin = center - V::one_hot(0) * (text_width(&label.0, LABEL_HEIGHT) / 2.0);
+            origin[1] = top + LABEL_HEIGHT;
+            selection_box.extend(
+                DrawLineCollection::from_lines(
+                    text_lines(&label.0, origin, V::one_hot(0), V::one_hot(1), LABEL_HEIGHT),
+                    SELECTION_COLOR,
+                )
+                .0
+                .into_iter(),
+            )
+        }
+        None => selection_box,
+    }
 }
 
 pub fn create_shape<V: VectorTrait>(
@@ -4,15 +4,16 @@ use std::marker::PhantomData;
 
 use crate::cleanup::DeletedEntities;
 use crate::config::Config;
-use crate::ecs_utils::ModSystem;
+use crate::ecs_utils::{ModSystem, SystemName};
 use crate::{components::*, config};
 use crate::{ecs_utils::Componentable, vector::VectorTrait};
 
 use super::input_to_transform::{pos_to_grid, reset_orientation_and_scale};
 use super::{
     cancel_manipulation, create_shape, delete_shape, duplicate_shape, manipulate_shape,
-    manipulate_shape_outer, print_debug, selection_box, set_manipulation_mode,
-    update_camera::update_camera, Input, MovementMode, ShapeManipulationState,
+    manipulate_shape_outer, print_debug, selection_box, set_manipulation_mode, toggle_fly,
+    toggle_hidden_axis_coloring, update_camera::update_camera, Input, MovementMode,
+    ShapeManipulationState,
 };
 
 pub struct UpdateCameraSystem<V>(pub PhantomData<V>);
@@ -248,6 +249,7 @@ where
     type SystemData = (
         ReadExpect<'a, Player>,
         ReadStorage<'a, Shape<V>>,
+        ReadStorage<'a, ShapeLabel>,
         WriteStorage<'a, MaybeSelected>,
         WriteStorage<'a, DrawLineCollection<V>>,
     );
@@ -257,6 +259,7 @@ where
         (
             player,
             read_shapes,
+            read_labels,
             mut write_maybe_selected,
             mut write_draw_line_collection,
         ): Self::SystemData,
@@ -267,7 +270,10 @@ where
                     write_draw_line_collection
                         .insert(
                             selected.entity,
-                            selection_box(read_shapes.get(selected.entity).unwrap()),
+                            selection_box(
+                                read_shapes.get(selected.entity).unwrap(),
+                                read_labels.get(selected.entity),
+                            ),
                         )
                         .expect("Couldn't add selection box!");
                 }
@@ -294,3 +300,27 @@ impl<'a, V: VectorTrait + Componentable> System<'a> for PrintDebugSystem<V> {
         print_debug::<V>(&mut input, &mut clip_state);
     }
 }
+
+pub struct ToggleHiddenAxisColoringSystem;
+impl<'a> System<'a> for ToggleHiddenAxisColoringSystem {
+    type SystemData = (Write<'a, Input>, WriteExpect<'a, Config>);
+
+    fn run(&mut self, (mut input, mut config): Self::SystemData) {
+        toggle_hidden_axis_coloring(&mut input, &mut config.view.hidden_axis);
+    }
+}
+impl SystemName for ToggleHiddenAxisColoringSystem {
+    const NAME: &'static str = "toggle_hidden_axis_coloring";
+}
+
+pub struct ToggleFlySystem;
+impl<'a> System<'a> for ToggleFlySystem {
+    type SystemData = (Write<'a, Input>, WriteExpect<'a, Config>);
+
+    fn run(&mut self, (mut input, mut config): Self::SystemData) {
+        toggle_fly(&mut input, &mut config.physics);
+    }
+}
+impl SystemName for ToggleFlySystem {
+    const NAME: &'static str = "toggle_fly";
+}
@@ -21,6 +21,7 @@ mod draw;
 mod geometry;
 mod vector;
 
+mod animation;
 mod build_level;
 mod cleanup;
 mod coin;
@@ -34,6 +34,7 @@ pub fn build_player<V>(
         })
         .with(camera)
         .with(MoveNext::<V>::default())
+        .with(VerticalMotion::default())
         .with(MaybeTarget::<V>(None))
         .with(MaybeSelected(None))
         .build();
@@ -149,3 +150,49 @@ where
         None => MaybeTarget(None),
     }
 }
+
+#[test]
+fn test_target_culled_single_face() {
+    use crate::draw::systems::VisibilitySystem;
+    use crate::geometry::shape::buildshapes::convex_shape_to_face_shape;
+    use crate::vector::{Mat3, Vec2, Vec3};
+    let mut world = World::new();
+    world.register::<Shape<Vec3>>();
+    world.register::<ShapeType<Vec3>>();
+    world.register::<ShapeClipState<Vec3>>();
+    world.register::<Transform<Vec3, Mat3>>();
+    world.insert(ClipState::<Vec3>::new());
+    let player = world
+        .create_entity()
+        .with(Transform::<Vec3, Mat3>::identity())
+        .build();
+    world.insert(Player(player));
+    // a square ahead of the player, along their view direction
+    let (mut shape, single_face) =
+        convex_shape_to_face_shape::<Vec3>(ShapeBuilder::<Vec2>::build_cube(2.0).build(), true);
+    shape.update_from_ref(&shape.clone(), &Transform::pos(Vec3::new(0.0, 0.0, 2.0)));
+    // marked out of view before its face visibility was ever computed
+    let e = world
+        .create_entity()
+        .with(shape)
+        .with(ShapeType::SingleFace(single_face))
+        .with(ShapeClipState::<Vec3> {
+            culled: true,
+            ..Default::default()
+        })
+        .build();
+    VisibilitySystem(PhantomData::<Vec3>).run_now(&world);
+
+    let (shapes, shape_types, shape_clip_states) = (
+        world.read_component::<Shape<Vec3>>(),
+        world.read_component::<ShapeType<Vec3>>(),
+        world.read_component::<ShapeClipState<Vec3>>(),
+    );
+    let target = shape_targeting(
+        &Transform::identity(),
+        (&shapes, &shape_types, &shape_clip_states, &world.entities()).join(),
+    );
+    assert!(target
+        .0
+        .is_some_and(|target| target.entity == e && (target.distance - 2.0).abs() < 1e-4));
+}
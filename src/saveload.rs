@@ -4,11 +4,12 @@ use serde::{de::DeserializeOwned, Serialize, Serializer};
 use specs::{
     saveload::{DeserializeComponents, SerializeComponents, SimpleMarker, SimpleMarkerAllocator},
     world::EntitiesRes,
-    Entities, ReadStorage, World, WorldExt, Write, WriteStorage,
+    Entities, Join, ReadExpect, ReadStorage, World, WorldExt, Write, WriteStorage,
 };
 
 use crate::{
-    components::{Shape, ShapeLabel, ShapeType, SingleFace, Transform},
+    components::{Shape, ShapeLabel, ShapeTexture, ShapeType, SingleFace, TextureLabel, Transform},
+    draw::{texture::library::TextureLibrary, FaceTexture},
     ecs_utils::Componentable,
     vector::VectorTrait,
 };
@@ -34,6 +35,7 @@ type DeseralizerReturns = SerializerReturns;
 type LevelSaveComponents<'a, V, M> = (
     ReadStorage<'a, Shape<V>>,
     ReadStorage<'a, ShapeLabel>,
+    ReadStorage<'a, TextureLabel>,
     ReadStorage<'a, Transform<V, M>>, //ReadStorage<'a, ShapeType<V>>,
                                       //ReadStorage<'a, SingleFace<V>>,
 );
@@ -41,6 +43,7 @@ type LevelSaveComponents<'a, V, M> = (
 type LevelLoadComponents<'a, V, M> = (
     WriteStorage<'a, Shape<V>>,
     WriteStorage<'a, ShapeLabel>,
+    WriteStorage<'a, TextureLabel>,
     WriteStorage<'a, Transform<V, M>>, //WriteStorage<'a, ShapeType<V>>,
                                        //WriteStorage<'a, SingleFace<V>>,
 );
@@ -61,7 +64,7 @@ where
         &markers,
         serializer,
     )
-    // eventually add hashmap of some sort for textures
+    // textures are saved as their TextureLabel, and rebuilt on load
     // save ref shapes + other resources
 }
 
@@ -71,22 +74,54 @@ pub fn load_level<V>(
 ) -> DeseralizerReturns
 where
     V: Componentable + Serialize + DeserializeOwned + VectorTrait,
+    V::SubV: Componentable,
     V::M: Componentable + Serialize + DeserializeOwned + Clone,
 {
-    let (mut load_storage, entities, mut marker_storage, mut marker_allocator) = world
-        .system_data::<(
-            LevelLoadComponents<V, V::M>,
-            Entities,
-            WriteStorage<SaveMarker>,
-            Write<SaveMarkerAllocator>,
-        )>();
-    DeserializeComponents::<Infallible, SaveMarker>::deserialize(
-        &mut load_storage,
-        &entities,
-        &mut marker_storage,
-        &mut marker_allocator,
-        deserializer,
-    )
+    {
+        let (mut load_storage, entities, mut marker_storage, mut marker_allocator) = world
+            .system_data::<(
+                LevelLoadComponents<V, V::M>,
+                Entities,
+                WriteStorage<SaveMarker>,
+                Write<SaveMarkerAllocator>,
+            )>();
+        DeserializeComponents::<Infallible, SaveMarker>::deserialize(
+            &mut load_storage,
+            &entities,
+            &mut marker_storage,
+            &mut marker_allocator,
+            deserializer,
+        )?;
+    }
+    rebuild_textures::<V>(world);
+    Ok(())
+}
+
+// gives each loaded shape with a TextureLabel the library texture on every face.
+// texture mappings, colors and tints applied when the level was built aren't saved
+fn rebuild_textures<V>(world: &mut World)
+where
+    V: Componentable + VectorTrait,
+    V::SubV: Componentable,
+{
+    let (shapes, texture_labels, mut shape_textures, textures, entities) = world.system_data::<(
+        ReadStorage<Shape<V>>,
+        ReadStorage<TextureLabel>,
+        WriteStorage<ShapeTexture<V::SubV>>,
+        ReadExpect<TextureLibrary>,
+        Entities,
+    )>();
+    for (shape, label, entity) in (&shapes, &texture_labels, &entities).join() {
+        let texture = textures.build::<V::SubV>(label);
+        let shape_texture =
+            ShapeTexture::new_default(shape.faces.len()).map_textures(|face_texture| FaceTexture {
+                texture: texture.clone(),
+                ..face_texture
+            });
+        shape_textures
+            .insert(entity, shape_texture)
+            .expect("loaded entity is alive");
+    }
 }
 
 fn mark_components(_world: &mut World) {
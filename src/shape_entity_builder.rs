@@ -1,7 +1,8 @@
 use crate::components::{
-    BBall, Convex, HasBBox, Shape, ShapeClipState, ShapeLabel, ShapeType, SingleFace,
-    StaticCollider, Transform, Transformable,
+    BBall, Convex, HasBBox, Shape, ShapeClipState, ShapeLabel, ShapeLinesCache, ShapeType,
+    SingleFace, StaticCollider, TextureLabel, Transform, Transformable,
 };
+use crate::draw::texture::library::TextureDescriptor;
 use crate::draw::{FaceTexture, ShapeTexture, Texture, TextureMapping};
 use crate::ecs_utils::Componentable;
 use crate::geometry::shape::{buildshapes, RefShapes};
@@ -20,6 +21,7 @@ pub struct ShapeEntityBuilder<V, U, M> {
     shape_label: ShapeLabel,
     pub transformation: Transform<V, M>,
     pub shape_texture: ShapeTexture<U>,
+    texture_label: Option<TextureLabel>,
     static_collider: Option<StaticCollider>,
 }
 
@@ -41,6 +43,7 @@ impl<V: VectorTrait> ShapeEntityBuilderV<V> {
             shape_label: label,
             transformation: Transform::identity(),
             shape_texture,
+            texture_label: None,
             static_collider: None,
         }
     }
@@ -52,15 +55,34 @@ impl<V: VectorTrait> ShapeEntityBuilderV<V> {
             shape_label: label,
             transformation: Transform::identity(),
             shape_texture: ShapeTexture::new_default(ref_shape.verts.len()),
+            texture_label: None,
             static_collider: None,
         }
     }
     pub fn with_texture(mut self, texture: ShapeTexture<V::SubV>) -> Self {
         self.shape_texture = texture;
+        self.texture_label = None;
         self
     }
     pub fn with_face_texture(mut self, face_texture: FaceTexture<V::SubV>) -> Self {
         self.shape_texture = self.shape_texture.with_texture(face_texture);
+        self.texture_label = None;
+        self
+    }
+    // replaces the texture of every face, keeping the texture mappings.
+    // the descriptor may not be in the library, so any texture label is dropped
+    pub fn with_texture_descriptor(mut self, descriptor: &TextureDescriptor) -> Self {
+        let texture = descriptor.build::<V::SubV>();
+        self.shape_texture = self.shape_texture.map_textures(|face_texture| FaceTexture {
+            texture: texture.clone(),
+            ..face_texture
+        });
+        self.texture_label = None;
+        self
+    }
+    // records which library texture the shape uses, so a saved level can rebuild it
+    pub fn with_texture_label(mut self, label: TextureLabel) -> Self {
+        self.texture_label = Some(label);
         self
     }
     pub fn with_texturing_fn<F>(mut self, f: F) -> Self
@@ -68,6 +90,7 @@ impl<V: VectorTrait> ShapeEntityBuilderV<V> {
         F: Fn(&Shape<V>) -> ShapeTexture<V::SubV>,
     {
         self.shape_texture = f(&self.shape);
+        self.texture_label = None;
         self
     }
     pub fn with_color(mut self, color: Color) -> Self {
@@ -75,6 +98,11 @@ impl<V: VectorTrait> ShapeEntityBuilderV<V> {
 
         self
     }
+    // multiplies the texture colors, keeping multicolored patterns
+    pub fn with_tint(mut self, tint: Color) -> Self {
+        self.shape_texture = self.shape_texture.with_tint(tint);
+        self
+    }
     pub fn with_collider(mut self, static_collider: Option<StaticCollider>) -> Self {
         self.static_collider = static_collider;
         self
@@ -97,6 +125,7 @@ where
             shape_label,
             transformation,
             shape_texture,
+            texture_label,
             static_collider,
         } = self;
         shape.update_from_ref(&shape.clone(), &transformation);
@@ -113,6 +142,8 @@ where
             .with(shape_label)
             .with(shape_texture)
             .with(ShapeClipState::<V>::default())
+            .with(ShapeLinesCache::<V>::default())
+            .maybe_with(texture_label)
             .maybe_with(static_collider)
             .marked::<SaveMarker>()
     }
@@ -123,6 +154,7 @@ where
             shape_label,
             transformation,
             shape_texture,
+            texture_label,
             static_collider,
         } = self;
         shape.update_from_ref(&shape.clone(), &transformation);
@@ -136,7 +168,11 @@ where
         lazy.insert(e, shape);
         lazy.insert(e, shape_texture);
         lazy.insert(e, ShapeClipState::<V>::default());
+        lazy.insert(e, ShapeLinesCache::<V>::default());
         lazy.insert(e, shape_label);
+        if let Some(label) = texture_label {
+            lazy.insert(e, label)
+        };
         if let Some(c) = static_collider {
             lazy.insert(e, c)
         };
@@ -1,6 +1,7 @@
 mod shape_systems;
 //for easy import of all systems
 pub use self::shape_systems::*;
+pub use crate::animation::AnimationSystem;
 pub use crate::cleanup::ShapeCleanupSystem;
 pub use crate::coin::{CoinSpinningSystem, PlayerCoinCollisionSystem};
 pub use crate::collide::systems::*;
@@ -3,10 +3,13 @@ use std::collections::{HashMap, HashSet};
 use specs::prelude::*;
 use specs::{ReadStorage, System, World, WriteStorage};
 
+use crate::collide::StaticCollider;
 use crate::components::{
-    BBox, HasBBox, MaybeSelected, Player, ShapeClipState, ShapeLabel, ShapeType,
+    BBox, HasBBox, MaybeSelected, Player, ShapeClipState, ShapeLabel, ShapeLinesCache,
+    ShapeTexture, ShapeType,
 };
-use crate::ecs_utils::Componentable;
+use crate::draw::clipping::bsp::BspTree;
+use crate::ecs_utils::{Componentable, SystemName};
 use crate::geometry::shape::RefShapes;
 use crate::vector::MatrixTrait;
 use crate::{
@@ -99,7 +102,7 @@ where
             (&self.0.modified, &mut write_shape_clip_state, &entities).join()
         {
             shape_clip_state.separators = HashMap::new();
-            shape_clip_state.in_front = HashSet::new();
+            shape_clip_state.in_front = Vec::new();
             entities_to_update.push(entity);
         }
         // clear separators within other shapes
@@ -118,6 +121,85 @@ where
     }
 }
 
+/// clears the cached lines of shapes whose geometry or texture has changed
+#[derive(Default)]
+pub struct UpdateLinesCacheSystem<V>(pub ModSystem<V>, pub ModSystem<V>);
+
+impl<'a, V> System<'a> for UpdateLinesCacheSystem<V>
+where
+    V: VectorTrait + Componentable,
+    V::SubV: Componentable,
+{
+    type SystemData = (
+        ReadStorage<'a, Shape<V>>,
+        ReadStorage<'a, ShapeTexture<V::SubV>>,
+        WriteStorage<'a, ShapeLinesCache<V>>,
+    );
+
+    fn run(&mut self, (read_shape, read_shape_texture, mut write_cache): Self::SystemData) {
+        self.0.gather_events(read_shape.channel());
+        self.1.gather_events(read_shape_texture.channel());
+        for (_, cache) in (
+            self.0.modified_or_inserted() | self.1.modified_or_inserted(),
+            &mut write_cache,
+        )
+            .join()
+        {
+            cache.clear();
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.0.reader_id = Some(WriteStorage::<Shape<V>>::fetch(world).register_reader());
+        self.1.reader_id =
+            Some(WriteStorage::<ShapeTexture<V::SubV>>::fetch(world).register_reader());
+    }
+}
+
+/// reinserts static shapes into the BSP tree whenever they are modified
+#[derive(Default)]
+pub struct UpdateBspTreeSystem<V>(pub ModSystem<V>);
+
+impl<'a, V> System<'a> for UpdateBspTreeSystem<V>
+where
+    V: VectorTrait + Componentable,
+{
+    type SystemData = (
+        ReadStorage<'a, Shape<V>>,
+        ReadStorage<'a, BBall<V>>,
+        ReadStorage<'a, StaticCollider>,
+        Write<'a, BspTree<V, Entity>>,
+        Entities<'a>,
+    );
+
+    fn run(
+        &mut self,
+        (read_shape, read_bball, read_static, mut bsp_tree, entities): Self::SystemData,
+    ) {
+        self.0.gather_events(read_shape.channel());
+        for (_, shape, bball, _, entity) in (
+            &self.0.modified_or_inserted(),
+            &read_shape,
+            &read_bball,
+            &read_static,
+            &entities,
+        )
+            .join()
+        {
+            bsp_tree.insert(entity, shape, bball);
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.0.reader_id = Some(WriteStorage::<Shape<V>>::fetch(world).register_reader());
+    }
+}
+impl SystemName for UpdateBspTreeSystem<()> {
+    const NAME: &'static str = "update_bsp_tree";
+}
+
 /// Updates shape components whenever the transform component is accessed mutably
 #[derive(Default)]
 pub struct TransformShapeSystem<V>(pub ModSystem<V>);
@@ -172,3 +254,94 @@ where
         self.0.reader_id = Some(WriteStorage::<Transform<V, V::M>>::fetch(world).register_reader());
     }
 }
+
+#[test]
+fn test_update_lines_cache() {
+    use crate::constants::FACE_SCALE;
+    use crate::draw::{calc_shapes_lines, clipping::ClipState, DrawLine};
+    use crate::geometry::shape::buildshapes::ShapeBuilder;
+    use crate::graphics::colors::RED;
+    use crate::vector::{Mat3, Vec2, Vec3};
+    use std::marker::PhantomData;
+
+    let mut world = World::new();
+    let ph = PhantomData::<Vec3>;
+    let mut system =
+        UpdateLinesCacheSystem(ModSystem::typed_default(ph), ModSystem::typed_default(ph));
+    System::setup(&mut system, &mut world);
+    world.register::<ShapeClipState<Vec3>>();
+    let shape = ShapeBuilder::<Vec3>::build_cube(1.0).build();
+    let n_faces = shape.faces.len();
+    let e = world
+        .create_entity()
+        .with(ShapeTexture::<Vec2>::new_default(n_faces))
+        .with(ShapeClipState::<Vec3> {
+            face_visibility: vec![true; n_faces],
+            ..Default::default()
+        })
+        .with(shape)
+        .with(ShapeLinesCache::<Vec3>::default())
+        .build();
+    let mut clip_state = ClipState::<Vec3>::new();
+    clip_state.clipping_enabled = false;
+    let calc_lines = |world: &World| {
+        calc_shapes_lines(
+            &world.read_component(),
+            &world.read_component(),
+            &world.read_component(),
+            &mut world.write_component(),
+            &[FACE_SCALE],
+            &clip_state,
+            None,
+        )
+    };
+    // lines computed from an empty cache
+    let fresh_lines = |world: &World| {
+        for cache in (&mut world.write_component::<ShapeLinesCache<Vec3>>()).join() {
+            cache.clear();
+        }
+        calc_lines(world)
+    };
+    let same = |a: &[DrawLine<Vec3>], b: &[DrawLine<Vec3>]| {
+        a.len() == b.len()
+            && a.iter()
+                .zip(b)
+                .all(|(a, b)| a.line.is_close(&b.line) && a.color.0 == b.color.0)
+    };
+
+    // cached lines are the same as the lines they were computed from
+    let lines = calc_lines(&world);
+    assert!(!lines.is_empty());
+    system.run_now(&world);
+    assert!(same(&calc_lines(&world), &lines));
+    system.run_now(&world);
+    assert!(same(&calc_lines(&world), &fresh_lines(&world)));
+
+    // moving the shape clears its lines
+    calc_lines(&world);
+    {
+        let mut shapes = world.write_component::<Shape<Vec3>>();
+        let shape = shapes.get_mut(e).unwrap();
+        shape.update_from_ref(
+            &shape.clone(),
+            &Transform::<Vec3, Mat3>::pos(Vec3::new(1.0, 0.0, 0.0)),
+        );
+    }
+    system.run_now(&world);
+    let moved_lines = calc_lines(&world);
+    assert!(!same(&moved_lines, &lines));
+    assert!(same(&moved_lines, &fresh_lines(&world)));
+
+    // so does changing its texture
+    world
+        .write_component()
+        .insert(
+            e,
+            ShapeTexture::<Vec2>::new_default(n_faces).with_color(RED),
+        )
+        .unwrap();
+    system.run_now(&world);
+    let red_lines = calc_lines(&world);
+    assert!(red_lines.iter().all(|line| line.color.0 == RED.0));
+    assert!(same(&red_lines, &fresh_lines(&world)));
+}
@@ -5,23 +5,27 @@ mod tests {
     use serde::{de::DeserializeOwned, Deserialize, Serialize};
     use specs::{
         saveload::{SerializeComponents, SimpleMarkerAllocator},
-        World, WorldExt,
+        Builder, Join, World, WorldExt,
     };
 
     use crate::{
-        build_level::{build_lvl_1, build_shape_library},
-        components::{Shape, ShapeLabel, Transform},
-        config::{self, save_config, Config},
+        build_level::{build_lvl_1, build_scene, build_shape_library},
+        collide::create_spatial_hash,
+        components::{ClipState, Shape, ShapeLabel, ShapeTexture, TextureLabel, Transform},
+        config::{self, save_config, Config, LevelConfig},
         constants::CUBE_LABEL_STR,
+        draw::{systems::CalcShapesLinesSystem, DrawLineList},
         engine::get_engine_dispatcher_builder,
         geometry::shape::{buildshapes::ShapeBuilder, RefShapes},
+        input::{Input, ShapeManipulationState},
         saveload::{load_level, save_level, Save, SaveMarker},
-        vector::{is_close, Mat3, Vec2, Vec3, VectorTrait},
+        vector::{is_close, Mat3, Vec2, Vec3, Vec4, VectorTrait},
     };
 
     fn new_world() -> World {
         let mut world = World::new();
         world.register::<SaveMarker>();
+        world.register::<TextureLabel>();
         //world.write_resource::<SimpleMarkerAllocator<Save>>();
         world.insert::<SimpleMarkerAllocator<Save>>(SimpleMarkerAllocator::default());
         let mut dispatcher = get_engine_dispatcher_builder::<Vec3>().build();
@@ -88,6 +92,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn serialize_textures() {
+        use crate::draw::{texture::library::build_texture_library, Texture};
+        use crate::shape_entity_builder::ShapeEntityBuilder;
+
+        let ref_shapes = build_shape_library::<Vec3>();
+        let textures = build_texture_library(1);
+        let label = TextureLabel::from_str("Checker");
+        let mut world = new_world();
+        let cube = ShapeEntityBuilder::new_convex_from_ref_shape(
+            &ref_shapes,
+            ShapeLabel::from_str(CUBE_LABEL_STR),
+        );
+        cube.clone()
+            .with_texture_descriptor(textures.get_unwrap(&label))
+            .with_texture_label(label.clone())
+            .build(&mut world)
+            .build();
+        cube.build(&mut world).build();
+
+        let mut serializer = serde_json::Serializer::new(Vec::new());
+        save_level::<Vec3>(&world, &mut serializer).unwrap();
+        let serialized = String::from_utf8(serializer.into_inner()).unwrap();
+        let mut loaded_world = new_world();
+        loaded_world.insert(textures);
+        let mut deserializer = serde_json::Deserializer::from_str(&serialized);
+        load_level::<Vec3>(&mut loaded_world, &mut deserializer).unwrap();
+
+        // only the labeled cube gets its texture back
+        let shapes = loaded_world.read_component::<Shape<Vec3>>();
+        let labels = loaded_world.read_component::<TextureLabel>();
+        let shape_textures = loaded_world.read_component::<ShapeTexture<Vec2>>();
+        assert_eq!(shapes.count(), 2);
+        assert_eq!(shape_textures.count(), 1);
+        for (shape, loaded_label, shape_texture) in (&shapes, &labels, &shape_textures).join() {
+            assert_eq!(loaded_label, &label);
+            assert_eq!(shape_texture.face_textures.len(), shape.faces.len());
+            for face_texture in &shape_texture.face_textures {
+                assert!(matches!(face_texture.texture, Texture::Lines { .. }));
+            }
+        }
+    }
+
     #[test]
     fn load_config() {
         println!("{:?}", config::load_config())
@@ -96,4 +143,130 @@ mod tests {
     fn test_save_config() {
         save_config(Config::default()).unwrap()
     }
+
+    #[test]
+    fn first_frame_keeps_player_on_floor() {
+        use crate::player::Player;
+
+        type V = Vec3;
+        let mut world = World::new();
+        world.register::<SaveMarker>();
+        world.insert::<SimpleMarkerAllocator<Save>>(SimpleMarkerAllocator::default());
+        let mut dispatcher = get_engine_dispatcher_builder::<V>().build();
+        dispatcher.setup(&mut world);
+        let mut config = Config::default();
+        config.scene.level = LevelConfig::Level1;
+        config.physics.fly = false;
+        // a slow first frame, long enough to fall through the floor in one step
+        let mut input = Input::new();
+        input.frame_duration = 0.5;
+        world.insert(input);
+        world.insert(ShapeManipulationState::default() as ShapeManipulationState<V, _>);
+        world.insert(config);
+        build_scene::<V>(&mut world);
+        create_spatial_hash::<V>(&mut world);
+        world.insert(ClipState::<V>::new());
+        world.insert(DrawLineList::<V>(vec![]));
+        world.insert(DrawLineList::<<V as VectorTrait>::SubV>(vec![]));
+        dispatcher.dispatch(&world);
+        world.maintain();
+
+        // the center floor's top is at y = -0.5
+        let player = world.read_resource::<Player>().0;
+        let pos = world
+            .read_component::<Transform<V, Mat3>>()
+            .get(player)
+            .unwrap()
+            .pos;
+        assert!(pos[1] > -0.5, "player fell to {}", pos);
+    }
+
+    #[test]
+    fn level_light() {
+        use crate::draw::light::LevelLight;
+
+        let level_light = |level: LevelConfig| {
+            let mut world = new_world();
+            let mut config = Config::default();
+            config.scene.level = level;
+            world.insert(config);
+            build_scene::<Vec3>(&mut world);
+            let light = world.read_resource::<LevelLight>().0.clone();
+            light
+        };
+        // the test level has its own light, and the others use the configured one
+        assert_ne!(
+            level_light(LevelConfig::Test1),
+            Config::default().scene.light
+        );
+        assert_eq!(
+            level_light(LevelConfig::Level1),
+            Config::default().scene.light
+        );
+    }
+
+    // run with `cargo test --release bench_calc_shapes_lines -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_calc_shapes_lines() {
+        use specs::rayon::ThreadPoolBuilder;
+        use specs::RunNow;
+        use std::marker::PhantomData;
+        use std::time::Instant;
+
+        type V = Vec4;
+        const FRAMES: u32 = 50;
+
+        let mut world = World::new();
+        world.register::<SaveMarker>();
+        world.insert::<SimpleMarkerAllocator<Save>>(SimpleMarkerAllocator::default());
+        let mut dispatcher = get_engine_dispatcher_builder::<V>().build();
+        dispatcher.setup(&mut world);
+        let mut config = Config::default();
+        config.scene.level = LevelConfig::Level1;
+        // keep the player in place while the first frames are dispatched
+        config.physics.fly = true;
+        world.insert(Input::new());
+        world.insert(ShapeManipulationState::default() as ShapeManipulationState<V, _>);
+        world.insert(config);
+        build_scene::<V>(&mut world);
+        create_spatial_hash::<V>(&mut world);
+        world.insert(ClipState::<V>::new());
+        world.insert(DrawLineList::<V>(vec![]));
+        world.insert(DrawLineList::<<V as VectorTrait>::SubV>(vec![]));
+        // populate visibility, clipping boundaries and occluders
+        for _ in 0..2 {
+            dispatcher.dispatch(&world);
+            world.maintain();
+        }
+
+        let time_frames = |num_threads: usize| {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap();
+            let mut system = CalcShapesLinesSystem(PhantomData::<V>);
+            let duration = pool.install(|| {
+                let start = Instant::now();
+                for _ in 0..FRAMES {
+                    system.run_now(&world);
+                }
+                start.elapsed() / FRAMES
+            });
+            (pool.current_num_threads(), duration)
+        };
+        let (_, serial) = time_frames(1);
+        let (num_threads, parallel) = time_frames(0);
+        let num_lines = world.read_resource::<DrawLineList<V>>().len();
+        // timing an empty frame would say nothing about the line stages
+        assert!(num_lines > 0);
+        println!(
+            "{} lines. 1 thread: {:?}, {} threads: {:?}, speedup: {:.2}",
+            num_lines,
+            serial,
+            num_threads,
+            parallel,
+            serial.as_secs_f64() / parallel.as_secs_f64()
+        );
+    }
 }